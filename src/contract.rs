@@ -1,66 +1,515 @@
-use crate::game::Player;
-use crate::state::GAMES;
+use crate::game::{Game, Player, BOARD_SIZE};
+use crate::state::{
+    Config, Games, ACTIVE_GAMES, CONFIG, DEFAULT_INVITE_TIMEOUT, DEFAULT_MOVE_TIMEOUT, GAMES,
+    NICKNAMES, PLAYER_STATS, TOTAL_COMPLETED, TOTAL_DRAWS, TOTAL_PAIRS,
+};
 use crate::{
     error::ContractError,
-    msg::{ExecuteMsg, InstantiateMsg, QueryMsg},
+    msg::{Action, ExecuteMsg, InstantiateMsg, QueryMsg},
+};
+use cosmwasm_std::{
+    ensure, to_json_binary, Addr, Api, Binary, Deps, DepsMut, Env, Event, MessageInfo, Order,
+    Response, StdResult, Storage, Timestamp,
 };
-use cosmwasm_std::{to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response};
+use cw_storage_plus::Bound;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+/// The name and version of the contract, stored with cw2.
+const CONTRACT_NAME: &str = concat!("crates.io:", env!("CARGO_PKG_NAME"));
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// The maximum number of characters of the reason given when rejecting an invitation.
+const MAX_REASON_LENGTH: usize = 128;
+
+/// The maximum number of characters of the message sent with an invitation.
+const MAX_INVITE_MESSAGE_LENGTH: usize = 256;
+
+/// The maximum number of characters of a nickname.
+const MAX_NICKNAME_LENGTH: usize = 32;
+
+/// The maximum number of characters of a chat message.
+const MAX_MESSAGE_LENGTH: usize = 280;
+
+/// The maximum number of chat messages kept between two players. Older messages are dropped rather than rejecting
+/// new ones, as the log is only informative.
+const MAX_CHAT_MESSAGES: usize = 20;
+
+/// The maximum number of pairs of players in a batch query, and of guests invited at once.
+const MAX_BATCH_SIZE: usize = 20;
+
+/// The default and maximum number of pairs of players listed by `AllPairs`, the maximum applying to `AllGamesList`
+/// too.
+const DEFAULT_PAIRS_LIMIT: u32 = 10;
+const MAX_PAIRS_LIMIT: u32 = 30;
+
+/// The maximum number of moves of a sequence, as many as cells on the board. Longer sequences are rejected with
+/// `CapacityExceeded` since they can't be played anyway.
+const MAX_SEQUENCE_MOVES: usize = BOARD_SIZE * BOARD_SIZE;
 
 pub fn instantiate(
-    _deps: DepsMut,
+    deps: DepsMut,
     _env: Env,
-    _info: MessageInfo,
-    _msg: InstantiateMsg,
+    info: MessageInfo,
+    msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
-    Ok(Response::default().add_attribute("action", "instantiate"))
+    nonpayable(&info)?;
+
+    let admin = match msg.admin {
+        Some(admin) => deps.api.addr_validate(&admin)?,
+        None => info.sender,
+    };
+    let config = Config {
+        admin,
+        invite_timeout: msg.invite_timeout.unwrap_or(DEFAULT_INVITE_TIMEOUT),
+        move_timeout: msg.move_timeout.unwrap_or(DEFAULT_MOVE_TIMEOUT),
+        store_history: msg.store_history.unwrap_or(true),
+        max_active_per_player: msg.max_active_per_player,
+        max_illegal_moves: msg.max_illegal_moves,
+        invite_cooldown: msg.invite_cooldown.unwrap_or(0),
+    };
+    validate_config(&config)?;
+    cw2::set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    CONFIG.save(deps.storage, &config)?;
+    TOTAL_COMPLETED.save(deps.storage, &0)?;
+    TOTAL_DRAWS.save(deps.storage, &0)?;
+    TOTAL_PAIRS.save(deps.storage, &0)?;
+
+    Ok(Response::default().add_attribute("action", Action::Instantiate))
 }
 
 pub fn execute(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     let api = deps.api;
 
+    // None of the execute messages expect funds, reject them rather than locking them in the contract.
+    nonpayable(&info)?;
+
     match msg {
-        ExecuteMsg::Invite { guest } => {
+        ExecuteMsg::Invite {
+            guest,
+            match_target,
+            symbol,
+            message,
+            time_bank,
+        } => {
+            let guest_addr = api.addr_validate(&guest)?;
+            exec::invite(
+                deps,
+                env,
+                info,
+                &guest_addr,
+                match_target,
+                symbol,
+                message,
+                time_bank,
+            )
+        }
+        ExecuteMsg::InviteMany { guests } => {
+            ensure!(
+                guests.len() <= MAX_BATCH_SIZE,
+                ContractError::BatchTooLarge {
+                    max: MAX_BATCH_SIZE
+                }
+            );
+            let guests = guests
+                .iter()
+                .map(|guest| api.addr_validate(guest))
+                .collect::<StdResult<Vec<_>>>()?;
+            exec::invite_many(deps, env, info, &guests)
+        }
+        ExecuteMsg::ExpireInvite { guest } => {
+            let guest_addr = api.addr_validate(&guest)?;
+            exec::expire_invite(deps, env, info, &guest_addr)
+        }
+        ExecuteMsg::Accept {
+            host,
+            preset,
+            requested_symbol,
+        } => {
+            let host_addr = api.addr_validate(&host)?;
+            exec::accept(deps, env, info, &host_addr, preset, requested_symbol)
+        }
+        ExecuteMsg::Reject { host, reason } => {
+            let host_addr = api.addr_validate(&host)?;
+            exec::reject(deps, info, &host_addr, reason)
+        }
+        ExecuteMsg::Play {
+            host,
+            guest,
+            cell,
+            nonce,
+        } => {
+            let host_addr = api.addr_validate(&host)?;
+            let guest_addr = api.addr_validate(&guest)?;
+            // Reject cells off the board before loading any state.
+            let cell = Game::check_cell(cell)?;
+            exec::play(deps, env, info, &host_addr, &guest_addr, cell, nonce)
+        }
+        ExecuteMsg::PlayAt {
+            host,
+            guest,
+            row,
+            col,
+        } => {
+            let host_addr = api.addr_validate(&host)?;
+            let guest_addr = api.addr_validate(&guest)?;
+            let cell = Game::cell_index(row, col)?;
+            exec::play(deps, env, info, &host_addr, &guest_addr, cell, None)
+        }
+        ExecuteMsg::PlaySequence { host, guest, cells } => {
+            let host_addr = api.addr_validate(&host)?;
+            let guest_addr = api.addr_validate(&guest)?;
+            // An empty sequence would only reset the clock of the player to move.
+            ensure!(!cells.is_empty(), ContractError::EmptySequence);
+            ensure!(
+                cells.len() <= MAX_SEQUENCE_MOVES,
+                ContractError::CapacityExceeded {
+                    resource: "moves".to_string()
+                }
+            );
+            exec::play_sequence(deps, env, info, &host_addr, &guest_addr, cells)
+        }
+        ExecuteMsg::TransferSeat {
+            host,
+            guest,
+            new_addr,
+        } => {
+            let host_addr = api.addr_validate(&host)?;
+            let guest_addr = api.addr_validate(&guest)?;
+            let new_addr = api.addr_validate(&new_addr)?;
+            exec::transfer_seat(deps, info, &host_addr, &guest_addr, &new_addr)
+        }
+        ExecuteMsg::Resign { host, guest } => {
+            let host_addr = api.addr_validate(&host)?;
             let guest_addr = api.addr_validate(&guest)?;
-            exec::invite(deps, info, &guest_addr)
+            exec::resign(deps, env, info, &host_addr, &guest_addr)
         }
-        ExecuteMsg::Accept { host } => {
+        ExecuteMsg::ConcedeMatch { host, guest } => {
             let host_addr = api.addr_validate(&host)?;
-            exec::accept(deps, info, &host_addr)
+            let guest_addr = api.addr_validate(&guest)?;
+            exec::concede_match(deps, env, info, &host_addr, &guest_addr)
+        }
+        ExecuteMsg::ClaimTimeout { host, guest } => {
+            let host_addr = api.addr_validate(&host)?;
+            let guest_addr = api.addr_validate(&guest)?;
+            exec::claim_timeout(deps, env, info, &host_addr, &guest_addr)
+        }
+        ExecuteMsg::ClaimResult { host, guest } => {
+            let host_addr = api.addr_validate(&host)?;
+            let guest_addr = api.addr_validate(&guest)?;
+            exec::claim_result(deps, env, info, &host_addr, &guest_addr)
+        }
+        ExecuteMsg::ForceEndGame { host, guest } => {
+            let host_addr = api.addr_validate(&host)?;
+            let guest_addr = api.addr_validate(&guest)?;
+            exec::force_end_game(deps, env, info, &host_addr, &guest_addr)
+        }
+        ExecuteMsg::ImportGame {
+            host,
+            guest,
+            host_role,
+            current,
+            completed,
+            force,
+        } => {
+            let host_addr = api.addr_validate(&host)?;
+            let guest_addr = api.addr_validate(&guest)?;
+            exec::import_game(
+                deps,
+                info,
+                &host_addr,
+                &guest_addr,
+                host_role,
+                current,
+                completed,
+                force,
+            )
+        }
+        ExecuteMsg::PurgePair { host, guest, force } => {
+            let host_addr = api.addr_validate(&host)?;
+            let guest_addr = api.addr_validate(&guest)?;
+            exec::purge_pair(deps, info, &host_addr, &guest_addr, force)
         }
-        ExecuteMsg::Reject { host } => {
+        ExecuteMsg::SetNickname { nickname } => exec::set_nickname(deps, info, nickname),
+        ExecuteMsg::PostMessage { host, guest, text } => {
             let host_addr = api.addr_validate(&host)?;
-            exec::reject(deps, info, &host_addr)
+            let guest_addr = api.addr_validate(&guest)?;
+            exec::post_message(deps, info, &host_addr, &guest_addr, text)
         }
-        ExecuteMsg::Play { host, guest, cell } => {
+        ExecuteMsg::ClearMyHistory { host, guest } => {
             let host_addr = api.addr_validate(&host)?;
             let guest_addr = api.addr_validate(&guest)?;
-            exec::play(deps, info, &host_addr, &guest_addr, cell)
+            exec::clear_my_history(deps, info, &host_addr, &guest_addr)
+        }
+        ExecuteMsg::UpdateConfig {
+            invite_timeout,
+            move_timeout,
+        } => exec::update_config(deps, info, invite_timeout, move_timeout),
+    }
+}
+
+/// Ensures that no funds were sent along with the message.
+fn nonpayable(info: &MessageInfo) -> Result<(), ContractError> {
+    ensure!(info.funds.is_empty(), ContractError::UnexpectedFunds);
+    Ok(())
+}
+
+/// Ensures that the configuration is valid.
+fn validate_config(config: &Config) -> Result<(), ContractError> {
+    ensure!(
+        config.invite_timeout > 0,
+        ContractError::InvalidConfig {
+            reason: "invite timeout must be greater than zero".to_string()
+        }
+    );
+    ensure!(
+        config.move_timeout > 0,
+        ContractError::InvalidConfig {
+            reason: "move timeout must be greater than zero".to_string()
+        }
+    );
+    Ok(())
+}
+
+/// Ensures that the player can start one more game without exceeding the limit of games in progress.
+fn ensure_active_games_limit(
+    storage: &dyn Storage,
+    player_addr: &Addr,
+) -> Result<(), ContractError> {
+    let limit = match CONFIG.load(storage)?.max_active_per_player {
+        Some(limit) => limit,
+        None => return Ok(()),
+    };
+
+    let active = ACTIVE_GAMES.may_load(storage, player_addr)?.unwrap_or(0);
+    ensure!(
+        active < limit,
+        ContractError::TooManyActiveGames {
+            player: player_addr.to_string(),
+            limit
+        }
+    );
+    Ok(())
+}
+
+/// Counts a game between the host and the guest in the games in progress of both players when it starts, or uncounts
+/// it when it ends. A player against themselves has a single game in progress.
+fn update_active_games(
+    storage: &mut dyn Storage,
+    host_addr: &Addr,
+    guest_addr: &Addr,
+    started: bool,
+) -> StdResult<()> {
+    let players = if host_addr == guest_addr {
+        vec![host_addr]
+    } else {
+        vec![host_addr, guest_addr]
+    };
+    for player_addr in players {
+        ACTIVE_GAMES.update(storage, player_addr, |active| -> StdResult<_> {
+            let active = active.unwrap_or(0);
+            // Games started before the counts were kept were never counted.
+            Ok(if started {
+                active + 1
+            } else {
+                active.saturating_sub(1)
+            })
+        })?;
+    }
+    Ok(())
+}
+
+/// Returns the symbol played by the player in the games between the host and the guest, given the host's symbol.
+fn player_role(
+    host_role: Player,
+    host_addr: &Addr,
+    guest_addr: &Addr,
+    player_addr: &Addr,
+) -> Result<Player, ContractError> {
+    if player_addr == host_addr {
+        Ok(host_role)
+    } else if player_addr == guest_addr {
+        Ok(host_role.opponent())
+    } else {
+        Err(ContractError::NotInvolved {
+            host: host_addr.to_string(),
+            guest: guest_addr.to_string(),
+            player: player_addr.to_string(),
+        })
+    }
+}
+
+/// Returns the nicknames of the host and the guest as attributes, for the players who set one.
+fn nickname_attributes(
+    storage: &dyn Storage,
+    host_addr: &Addr,
+    guest_addr: &Addr,
+) -> StdResult<Vec<(&'static str, String)>> {
+    let mut attributes = vec![];
+    if let Some(nickname) = NICKNAMES.may_load(storage, host_addr)? {
+        attributes.push(("host_nickname", nickname));
+    }
+    if let Some(nickname) = NICKNAMES.may_load(storage, guest_addr)? {
+        attributes.push(("guest_nickname", nickname));
+    }
+    Ok(attributes)
+}
+
+/// Returns a short reference to the game at the given index between two players, for indexers to group events.
+///
+/// The reference is the first 8 hex characters of the SHA-256 of the host and guest addresses, in the order the pair
+/// is stored, and the index. Each address is prefixed by its length so that no two pairs hash the same bytes.
+pub(crate) fn game_ref(host_addr: &Addr, guest_addr: &Addr, index: u64) -> String {
+    let mut hasher = Sha256::new();
+    for addr in [host_addr, guest_addr] {
+        hasher.update((addr.as_str().len() as u64).to_be_bytes());
+        hasher.update(addr.as_bytes());
+    }
+    hasher.update(index.to_be_bytes());
+    hasher.finalize()[..4]
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// Validates the addresses of the pair a page of pairs starts after.
+fn validate_cursor(
+    api: &dyn Api,
+    start_after: Option<(String, String)>,
+) -> StdResult<Option<(Addr, Addr)>> {
+    start_after
+        .map(|(host, guest)| Ok((api.addr_validate(&host)?, api.addr_validate(&guest)?)))
+        .transpose()
+}
+
+/// Returns the cursor of the next page if the page is full, i.e. the last pair of the page.
+fn next_cursor(pairs: &[(String, String)], limit: Option<usize>) -> Option<(String, String)> {
+    match limit {
+        Some(limit) if pairs.len() == limit => pairs.last().cloned(),
+        _ => None,
+    }
+}
+
+/// Returns the address of the player who won a game between the host and the guest, if any.
+///
+/// The winner is resolved with the symbol the host played in that game, falling back to their current one for games
+/// completed without a record of it.
+fn winner_addr(
+    game: &Game,
+    host_role: Player,
+    host_addr: &Addr,
+    guest_addr: &Addr,
+) -> Option<String> {
+    let host_role = game.host_role.unwrap_or(host_role);
+    game.result().winner().map(|winner| {
+        if winner == host_role {
+            host_addr.to_string()
+        } else {
+            guest_addr.to_string()
+        }
+    })
+}
+
+/// Returns the error for a pair without a game in progress, hinting at a rematch when games were completed.
+fn no_game_in_progress(host_addr: &Addr, guest_addr: &Addr, games: &Games) -> ContractError {
+    if games.completed.is_empty() {
+        ContractError::NoGameInProgress {
+            host: host_addr.to_string(),
+            guest: guest_addr.to_string(),
+        }
+    } else {
+        ContractError::GameFinished {
+            host: host_addr.to_string(),
+            guest: guest_addr.to_string(),
+            completed: games.completed.len(),
         }
     }
 }
 
 mod exec {
     use super::*;
-    use crate::{game::Game, state::Games};
-    use cosmwasm_std::ensure;
-    use std::{
-        collections::hash_map::DefaultHasher,
-        hash::{Hash, Hasher},
+    use crate::{
+        game::{GameError, GameResult},
+        state::TOTAL_COMPLETED,
     };
 
+    #[allow(clippy::too_many_arguments)]
     pub fn invite(
         deps: DepsMut,
+        env: Env,
         info: MessageInfo,
         guest_addr: &Addr,
+        match_target: Option<u32>,
+        symbol: Option<Player>,
+        message: Option<String>,
+        time_bank: Option<u64>,
     ) -> Result<Response, ContractError> {
+        ensure!(match_target != Some(0), ContractError::InvalidMatchTarget);
+        ensure!(time_bank != Some(0), ContractError::InvalidTimeBank);
+        ensure!(symbol != Some(Player::None), GameError::InvalidPlayer);
+        if let Some(message) = &message {
+            ensure!(
+                message.chars().count() <= MAX_INVITE_MESSAGE_LENGTH,
+                ContractError::MessageTooLong {
+                    max: MAX_INVITE_MESSAGE_LENGTH
+                }
+            );
+        }
+
+        // The guest already invited the sender: both want to play, so the invitation is accepted instead. The
+        // counter-invitation can only repeat its terms, a symbol is requested as on `Accept`.
+        if let Some(mut games) = GAMES.may_load(deps.storage, (guest_addr, &info.sender))? {
+            if games.pending_invition && *guest_addr != info.sender {
+                ensure!(
+                    match_target.is_none_or(|target| Some(target) == games.match_target)
+                        && time_bank.is_none_or(|bank| Some(bank) == games.time_bank)
+                        && message.is_none(),
+                    ContractError::TermsMismatch {
+                        host: guest_addr.to_string(),
+                        guest: info.sender.to_string(),
+                    }
+                );
+                if let Some(symbol) = symbol {
+                    ensure!(
+                        !(games.host_chose_symbol && games.host == symbol),
+                        ContractError::SymbolConflict { symbol }
+                    );
+                    games.host = symbol.opponent();
+                }
+                ensure_active_games_limit(deps.storage, guest_addr)?;
+                ensure_active_games_limit(deps.storage, &info.sender)?;
+                start_game(
+                    deps.storage,
+                    &env,
+                    guest_addr,
+                    &info.sender,
+                    &mut games,
+                    Game::new(),
+                )?;
+                GAMES.save(deps.storage, (guest_addr, &info.sender), &games)?;
+
+                return Ok(Response::default()
+                    .add_attribute("action", Action::MutualAccept)
+                    .add_attribute("host", guest_addr.to_string())
+                    .add_attribute("guest", info.sender.to_string())
+                    .add_attribute(
+                        "game_ref",
+                        game_ref(guest_addr, &info.sender, games.completed_count()),
+                    ));
+            }
+        }
+
         let games = GAMES.load(deps.storage, (&info.sender, guest_addr));
 
-        let games = if let Ok(mut games) = games {
+        let (games, is_new_pair) = if let Ok(mut games) = games {
             // Ensure that there is no game in progress. Otherwise, return an error.
             ensure!(
                 games.current.is_none(),
@@ -69,32 +518,161 @@ mod exec {
                     guest: guest_addr.to_string()
                 }
             );
+            // Ensure that the guest has not already been invited.
+            ensure!(
+                !games.pending_invition,
+                ContractError::InvitationAlreadyPending {
+                    host: info.sender.to_string(),
+                    guest: guest_addr.to_string()
+                }
+            );
+            let config = CONFIG.load(deps.storage)?;
+            if let Some(last_invited_at) = games.last_invited_at {
+                let available_at = last_invited_at.plus_seconds(config.invite_cooldown);
+                ensure!(
+                    env.block.time >= available_at,
+                    ContractError::InviteCooldown {
+                        remaining: available_at.seconds() - env.block.time.seconds()
+                    }
+                );
+            }
             // Set pending_invition to true. The game will be created when the guest accepts the invitation
             games.pending_invition = true;
-            games
+            games.invited_at = Some(env.block.time);
+            games.last_invited_at = Some(env.block.time);
+            games.invite_message = message.clone();
+            // A new invitation starts a new match.
+            games.match_target = match_target;
+            games.time_bank = time_bank;
+            games.host_score = 0;
+            games.guest_score = 0;
+            if let Some(symbol) = symbol {
+                games.host = symbol;
+            }
+            games.host_chose_symbol = symbol.is_some();
+            (games, false)
         } else {
-            Games {
+            let host = symbol.unwrap_or_else(|| get_host_role(&info.sender, guest_addr));
+            let games = Games {
                 pending_invition: true,
-                host: get_host_role(&info.sender, guest_addr),
-                current: None,
-                completed: vec![],
+                invited_at: Some(env.block.time),
+                last_invited_at: Some(env.block.time),
+                invite_message: message.clone(),
+                host_chose_symbol: symbol.is_some(),
+                match_target,
+                time_bank,
+                ..Games::new(host)
+            };
+            TOTAL_PAIRS.update(deps.storage, |total| -> StdResult<_> { Ok(total + 1) })?;
+            (games, true)
+        };
+
+        GAMES.save(deps.storage, (&info.sender, guest_addr), &games)?;
+
+        let mut response = Response::default()
+            .add_attribute("action", Action::Invite)
+            .add_attribute("host", info.sender.to_string())
+            .add_attribute("guest", guest_addr.to_string())
+            .add_attribute("is_new_pair", is_new_pair.to_string())
+            .add_attribute(
+                "game_ref",
+                game_ref(&info.sender, guest_addr, games.completed_count()),
+            )
+            .add_attributes(nickname_attributes(deps.storage, &info.sender, guest_addr)?);
+        if let Some(message) = message {
+            response = response.add_attribute("invite_message", message);
+        }
+        Ok(response)
+    }
+
+    pub fn invite_many(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        guests: &[Addr],
+    ) -> Result<Response, ContractError> {
+        let mut events = vec![];
+        let mut skipped = vec![];
+        for guest_addr in guests {
+            // A failed invitation doesn't write anything, so the batch can go on with the next guest.
+            match invite(
+                deps.branch(),
+                env.clone(),
+                info.clone(),
+                guest_addr,
+                None,
+                None,
+                None,
+                None,
+            ) {
+                Ok(response) => {
+                    events.push(Event::new("invite").add_attributes(response.attributes))
+                }
+                Err(_) => skipped.push(guest_addr.to_string()),
+            }
+        }
+
+        Ok(Response::default()
+            .add_attribute("action", Action::InviteMany)
+            .add_attribute("host", info.sender.to_string())
+            .add_attribute("invited", events.len().to_string())
+            .add_attribute("skipped", skipped.join(","))
+            .add_events(events))
+    }
+
+    pub fn expire_invite(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        guest_addr: &Addr,
+    ) -> Result<Response, ContractError> {
+        let mut games = GAMES.load(deps.storage, (&info.sender, guest_addr))?;
+
+        let invited_at = match games.invited_at {
+            Some(invited_at) if games.pending_invition => invited_at,
+            _ => {
+                return Err(ContractError::NoPendingInvitation {
+                    host: info.sender.to_string(),
+                    guest: guest_addr.to_string(),
+                })
             }
         };
 
+        let config = CONFIG.load(deps.storage)?;
+        ensure!(
+            env.block.time >= invited_at.plus_seconds(config.invite_timeout),
+            ContractError::InvitationNotExpired {
+                host: info.sender.to_string(),
+                guest: guest_addr.to_string()
+            }
+        );
+
+        games.pending_invition = false;
+        games.invited_at = None;
+        games.invite_message = None;
+
         GAMES.save(deps.storage, (&info.sender, guest_addr), &games)?;
 
         Ok(Response::default()
-            .add_attribute("action", "invite")
+            .add_attribute("action", Action::ExpireInvite)
             .add_attribute("host", info.sender.to_string())
             .add_attribute("guest", guest_addr.to_string()))
     }
 
     pub fn accept(
         deps: DepsMut,
+        env: Env,
         info: MessageInfo,
         host_addr: &Addr,
+        preset: Option<Vec<(usize, Player)>>,
+        requested_symbol: Option<Player>,
     ) -> Result<Response, ContractError> {
-        let mut games = GAMES.load(deps.storage, (host_addr, &info.sender))?;
+        let mut games = GAMES
+            .may_load(deps.storage, (host_addr, &info.sender))?
+            .ok_or_else(|| ContractError::NotInvited {
+                host: host_addr.to_string(),
+                player: info.sender.to_string(),
+            })?;
 
         ensure!(
             games.pending_invition,
@@ -104,22 +682,64 @@ mod exec {
             }
         );
 
-        games.pending_invition = false;
-        games.current = Some(Game::new());
+        let game = match preset {
+            Some(preset) => Game::from_preset(&preset).ok_or(ContractError::IllegalPreset)?,
+            None => Game::new(),
+        };
+
+        // The guest gets the requested symbol unless the host chose it. X still plays first.
+        if let Some(symbol) = requested_symbol {
+            ensure!(symbol != Player::None, GameError::InvalidPlayer);
+            ensure!(
+                !(games.host_chose_symbol && games.host == symbol),
+                ContractError::SymbolConflict { symbol }
+            );
+            games.host = symbol.opponent();
+        }
+
+        ensure_active_games_limit(deps.storage, host_addr)?;
+        ensure_active_games_limit(deps.storage, &info.sender)?;
+        start_game(
+            deps.storage,
+            &env,
+            host_addr,
+            &info.sender,
+            &mut games,
+            game,
+        )?;
 
         GAMES.save(deps.storage, (host_addr, &info.sender), &games)?;
         Ok(Response::default()
-            .add_attribute("action", "accept invitation")
+            .add_attribute("action", Action::AcceptInvite)
             .add_attribute("host", host_addr.to_string())
-            .add_attribute("guest", info.sender.to_string()))
+            .add_attribute("guest", info.sender.to_string())
+            .add_attribute(
+                "game_ref",
+                game_ref(host_addr, &info.sender, games.completed_count()),
+            ))
     }
 
     pub fn reject(
         deps: DepsMut,
         info: MessageInfo,
         host_addr: &Addr,
+        reason: Option<String>,
     ) -> Result<Response, ContractError> {
-        let mut games = GAMES.load(deps.storage, (host_addr, &info.sender))?;
+        if let Some(reason) = &reason {
+            ensure!(
+                reason.chars().count() <= MAX_REASON_LENGTH,
+                ContractError::ReasonTooLong {
+                    max: MAX_REASON_LENGTH
+                }
+            );
+        }
+
+        let mut games = GAMES
+            .may_load(deps.storage, (host_addr, &info.sender))?
+            .ok_or_else(|| ContractError::NotInvited {
+                host: host_addr.to_string(),
+                player: info.sender.to_string(),
+            })?;
 
         ensure!(
             games.pending_invition,
@@ -129,143 +749,1946 @@ mod exec {
             }
         );
         games.pending_invition = false;
+        games.invited_at = None;
+        games.invite_message = None;
 
         GAMES.save(deps.storage, (host_addr, &info.sender), &games)?;
 
-        Ok(Response::default()
-            .add_attribute("action", "reject invitation")
+        let mut response = Response::default()
+            .add_attribute("action", Action::RejectInvite)
             .add_attribute("host", host_addr.to_string())
-            .add_attribute("guest", info.sender.to_string()))
+            .add_attribute("guest", info.sender.to_string());
+        if let Some(reason) = reason {
+            response = response.add_attribute("reason", reason);
+        }
+        Ok(response)
     }
 
     pub fn play(
         deps: DepsMut,
+        env: Env,
         info: MessageInfo,
         host_addr: &Addr,
         guest_addr: &Addr,
         cell: usize,
+        nonce: Option<u64>,
     ) -> Result<Response, ContractError> {
+        // The roles of a player against themselves can't be told apart, such games are played with `PlaySequence`.
+        ensure!(
+            host_addr != guest_addr,
+            ContractError::SelfPlay {
+                player: host_addr.to_string()
+            }
+        );
         let mut games = GAMES.load(deps.storage, (host_addr, guest_addr))?;
 
-        let game = games
-            .current
-            .as_mut()
-            .ok_or(ContractError::NoGameInProgress {
-                host: host_addr.to_string(),
-                guest: guest_addr.to_string(),
-            })?;
+        ensure!(
+            games.current.is_some(),
+            no_game_in_progress(host_addr, guest_addr, &games)
+        );
 
-        let player = if info.sender == *host_addr {
-            games.host
-        } else if info.sender == *guest_addr {
-            if games.host == Player::O {
-                Player::X
-            } else {
-                Player::O
-            }
-        } else {
-            return Err(ContractError::NotInvolved {
-                host: host_addr.to_string(),
-                guest: guest_addr.to_string(),
-                player: info.sender.to_string(),
-            });
-        };
+        let player = player_role(games.host, host_addr, guest_addr, &info.sender)?;
+        let outcome = play_move(
+            deps.storage,
+            &mut games,
+            player,
+            cell,
+            nonce,
+            env.block.time,
+        )?;
+        if let MoveOutcome::Illegal {
+            reason,
+            count,
+            forfeit,
+        } = outcome
+        {
+            let events = complete_if_over(deps.storage, &env, host_addr, guest_addr, &mut games)?;
 
-        game.play(player, cell)?;
+            GAMES.save(deps.storage, (host_addr, guest_addr), &games)?;
 
-        if game.is_over() {
-            games.completed.push(*game);
-            games.current = None;
+            return Ok(Response::default()
+                .add_events(events)
+                .add_attribute("action", Action::IllegalMove)
+                .add_attribute("host", host_addr.to_string())
+                .add_attribute("guest", guest_addr.to_string())
+                .add_attribute("player", info.sender.to_string())
+                .add_attribute("cell", cell.to_string())
+                .add_attribute("reason", reason.to_string())
+                .add_attribute("illegal_moves", count.to_string())
+                .add_attribute("forfeit", forfeit.to_string()));
         }
+        let game_ref = game_ref(host_addr, guest_addr, games.completed_count());
+
+        // Resolve the player whose turn is next back to an address.
+        let next_turn = match games.current.filter(|game| !game.is_over()) {
+            Some(game) if game.turn == games.host => host_addr.to_string(),
+            Some(_) => guest_addr.to_string(),
+            None => "none".to_string(),
+        };
+
+        let events = complete_if_over(deps.storage, &env, host_addr, guest_addr, &mut games)?;
 
         GAMES.save(deps.storage, (host_addr, guest_addr), &games)?;
 
         Ok(Response::default()
-            .add_attribute("action", "play")
+            .add_events(events)
+            .add_attribute("action", Action::Play)
             .add_attribute("host", host_addr.to_string())
             .add_attribute("guest", guest_addr.to_string())
-            .add_attribute("cell", cell.to_string()))
+            .add_attribute("cell", cell.to_string())
+            .add_attribute("next_turn", next_turn)
+            .add_attribute("game_ref", game_ref)
+            .add_attributes(nickname_attributes(deps.storage, host_addr, guest_addr)?))
     }
 
-    /// Get the host role based on the hash of the inviter and guest addresses.
-    ///
-    /// The first bit of the hash of the two addresses is used to determine the host symbol. If the first bit is 0,
-    /// the host symbol is O, otherwise it is X.
-    fn get_host_role(host_addr: &Addr, guest_addr: &Addr) -> Player {
-        let concat = format!("{host_addr}{guest_addr}");
-        let mut hasher = DefaultHasher::new();
-        concat.hash(&mut hasher);
-        let hash = hasher.finish().to_string();
+    pub fn play_sequence(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        host_addr: &Addr,
+        guest_addr: &Addr,
+        cells: Vec<usize>,
+    ) -> Result<Response, ContractError> {
+        let mut games = GAMES.load(deps.storage, (host_addr, guest_addr))?;
 
-        let first_bit = hash.as_bytes()[0] & 1;
-        if first_bit == 0 {
-            Player::O
-        } else {
-            Player::X
+        ensure!(
+            games.current.is_some(),
+            no_game_in_progress(host_addr, guest_addr, &games)
+        );
+
+        ensure!(
+            info.sender == *host_addr || info.sender == *guest_addr,
+            ContractError::NotInvolved {
+                host: host_addr.to_string(),
+                guest: guest_addr.to_string(),
+                player: info.sender.to_string(),
+            }
+        );
+
+        let mut moves = 0;
+        let mut illegal = None;
+        for &cell in &cells {
+            // The game must still be in progress for every move of the sequence.
+            let game = games
+                .current
+                .filter(|game| !game.is_over())
+                .ok_or_else(|| ContractError::NoGameInProgress {
+                    host: host_addr.to_string(),
+                    guest: guest_addr.to_string(),
+                })?;
+
+            // The sender must be the player responsible for the move.
+            let player = game.turn;
+            let player_addr = if player == games.host {
+                host_addr
+            } else {
+                guest_addr
+            };
+            ensure!(info.sender == *player_addr, GameError::NotYourTurn);
+
+            // A counted illegal move ends the sequence, the moves before it are kept.
+            match play_move(deps.storage, &mut games, player, cell, None, env.block.time)? {
+                MoveOutcome::Played => moves += 1,
+                outcome => {
+                    illegal = Some(outcome);
+                    break;
+                }
+            }
         }
-    }
-}
 
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
-    match msg {
-        QueryMsg::Games { host, guest } => {
-            let host_addr = deps.api.addr_validate(&host)?;
-            let guest_addr = deps.api.addr_validate(&guest)?;
-            Ok(to_binary(&query::games(deps, &host_addr, &guest_addr)?)?)
+        let events = complete_if_over(deps.storage, &env, host_addr, guest_addr, &mut games)?;
+
+        GAMES.save(deps.storage, (host_addr, guest_addr), &games)?;
+
+        let mut response = Response::default()
+            .add_events(events)
+            .add_attribute("action", Action::PlaySequence)
+            .add_attribute("host", host_addr.to_string())
+            .add_attribute("guest", guest_addr.to_string())
+            .add_attribute("moves", moves.to_string());
+        if let Some(MoveOutcome::Illegal {
+            reason,
+            count,
+            forfeit,
+        }) = illegal
+        {
+            response = response
+                .add_attribute("reason", reason.to_string())
+                .add_attribute("illegal_moves", count.to_string())
+                .add_attribute("forfeit", forfeit.to_string());
         }
-        QueryMsg::AllGamesList {} => Ok(to_binary(&query::all_games_list(deps)?)?),
+        Ok(response)
     }
-}
-
-mod query {
-    use super::*;
-    use crate::msg::{AllGamesListResponse, GamesInfo, GamesResponse};
-    use cosmwasm_std::{Order, StdResult};
 
-    pub fn games(
-        deps: Deps,
+    pub fn transfer_seat(
+        deps: DepsMut,
+        info: MessageInfo,
         host_addr: &Addr,
         guest_addr: &Addr,
-    ) -> Result<GamesResponse, ContractError> {
+        new_addr: &Addr,
+    ) -> Result<Response, ContractError> {
         let games = GAMES.load(deps.storage, (host_addr, guest_addr))?;
 
-        let game_info = GamesInfo {
-            host: host_addr.to_string(),
+        ensure!(
+            games.current.is_some(),
+            ContractError::NoGameInProgress {
+                host: host_addr.to_string(),
+                guest: guest_addr.to_string(),
+            }
+        );
+
+        // The seat keeps its role, only the address sitting on it changes.
+        let (new_host, new_guest) = if info.sender == *host_addr {
+            (new_addr, guest_addr)
+        } else if info.sender == *guest_addr {
+            (host_addr, new_addr)
+        } else {
+            return Err(ContractError::NotInvolved {
+                host: host_addr.to_string(),
+                guest: guest_addr.to_string(),
+                player: info.sender.to_string(),
+            });
+        };
+
+        ensure!(
+            new_host != new_guest,
+            ContractError::SelfPlay {
+                player: new_addr.to_string()
+            }
+        );
+        ensure!(
+            !GAMES.has(deps.storage, (new_host, new_guest)),
+            ContractError::PairAlreadyExists {
+                host: new_host.to_string(),
+                guest: new_guest.to_string(),
+            }
+        );
+
+        // The game in progress counts against the limit of the player taking the seat.
+        ensure_active_games_limit(deps.storage, new_addr)?;
+
+        GAMES.remove(deps.storage, (host_addr, guest_addr));
+        GAMES.save(deps.storage, (new_host, new_guest), &games)?;
+        update_active_games(deps.storage, host_addr, guest_addr, false)?;
+        update_active_games(deps.storage, new_host, new_guest, true)?;
+
+        Ok(Response::default()
+            .add_attribute("action", Action::TransferSeat)
+            .add_attribute("host", new_host.to_string())
+            .add_attribute("guest", new_guest.to_string())
+            .add_attribute("from", info.sender.to_string())
+            .add_attribute("to", new_addr.to_string()))
+    }
+
+    pub fn resign(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        host_addr: &Addr,
+        guest_addr: &Addr,
+    ) -> Result<Response, ContractError> {
+        let mut games = GAMES.load(deps.storage, (host_addr, guest_addr))?;
+
+        let no_game = no_game_in_progress(host_addr, guest_addr, &games);
+        let game = games.current.as_mut().ok_or(no_game)?;
+
+        let player = player_role(games.host, host_addr, guest_addr, &info.sender)?;
+
+        game.abandon(Some(player));
+
+        let events = complete_if_over(deps.storage, &env, host_addr, guest_addr, &mut games)?;
+
+        GAMES.save(deps.storage, (host_addr, guest_addr), &games)?;
+
+        Ok(Response::default()
+            .add_events(events)
+            .add_attribute("action", Action::Resign)
+            .add_attribute("host", host_addr.to_string())
+            .add_attribute("guest", guest_addr.to_string())
+            .add_attribute("player", info.sender.to_string()))
+    }
+
+    pub fn concede_match(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        host_addr: &Addr,
+        guest_addr: &Addr,
+    ) -> Result<Response, ContractError> {
+        let mut games = GAMES.load(deps.storage, (host_addr, guest_addr))?;
+
+        let target = games.match_target.ok_or(ContractError::NotInMatch {
+            host: host_addr.to_string(),
             guest: guest_addr.to_string(),
-            host_role: games.host,
-            guest_role: if games.host == Player::O {
-                Player::X
+        })?;
+        let no_game = no_game_in_progress(host_addr, guest_addr, &games);
+        let game = games.current.as_mut().ok_or(no_game)?;
+
+        let player = player_role(games.host, host_addr, guest_addr, &info.sender)?;
+
+        // The game in progress is lost like a resignation, then the opponent wins the match whatever the score.
+        game.abandon(Some(player));
+        // Only the `game_over` event is kept, the outcome of the match is described by the response.
+        let game_over = complete_if_over(deps.storage, &env, host_addr, guest_addr, &mut games)?
+            .into_iter()
+            .filter(|event| event.ty == "game_over");
+        let winner_addr = if player == games.host {
+            games.guest_score = target;
+            guest_addr
+        } else {
+            games.host_score = target;
+            host_addr
+        };
+        // The next game of the match may have started already, it ends with the match.
+        if games.current.take().is_some() {
+            update_active_games(deps.storage, host_addr, guest_addr, false)?;
+        }
+
+        GAMES.save(deps.storage, (host_addr, guest_addr), &games)?;
+
+        Ok(Response::default()
+            .add_events(game_over)
+            .add_attribute("action", Action::MatchConceded)
+            .add_attribute("host", host_addr.to_string())
+            .add_attribute("guest", guest_addr.to_string())
+            .add_attribute("player", info.sender.to_string())
+            .add_attribute("winner", winner_addr.to_string())
+            .add_attribute("host_score", games.host_score.to_string())
+            .add_attribute("guest_score", games.guest_score.to_string()))
+    }
+
+    pub fn claim_timeout(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        host_addr: &Addr,
+        guest_addr: &Addr,
+    ) -> Result<Response, ContractError> {
+        let mut games = GAMES.load(deps.storage, (host_addr, guest_addr))?;
+
+        let no_game = no_game_in_progress(host_addr, guest_addr, &games);
+        let game = games.current.as_mut().ok_or(no_game)?;
+
+        let player = player_role(games.host, host_addr, guest_addr, &info.sender)?;
+        // Only the player waiting for the opponent's move can claim.
+        ensure!(game.turn != player, ContractError::ClaimOnOwnTurn);
+
+        let since = game
+            .last_move_at
+            .or(game.created_at)
+            .unwrap_or(env.block.time);
+        // With a time bank, the player to move has until their bank runs out.
+        let timeout = if games.time_bank.is_some() {
+            if game.turn == games.host {
+                games.host_time_left
+            } else {
+                games.guest_time_left
+            }
+        } else {
+            CONFIG.load(deps.storage)?.move_timeout
+        };
+        let deadline = since.plus_seconds(timeout);
+        ensure!(
+            env.block.time >= deadline,
+            ContractError::MoveNotTimedOut { deadline }
+        );
+
+        // The player who let the time run out loses the game.
+        game.abandon(Some(game.turn));
+        let timed_out_addr = if info.sender == *host_addr {
+            guest_addr
+        } else {
+            host_addr
+        };
+        PLAYER_STATS.update(deps.storage, timed_out_addr, |stats| -> StdResult<_> {
+            let mut stats = stats.unwrap_or_default();
+            stats.timeouts += 1;
+            Ok(stats)
+        })?;
+
+        let events = complete_if_over(deps.storage, &env, host_addr, guest_addr, &mut games)?;
+
+        GAMES.save(deps.storage, (host_addr, guest_addr), &games)?;
+
+        Ok(Response::default()
+            .add_events(events)
+            .add_attribute("action", Action::ClaimTimeout)
+            .add_attribute("host", host_addr.to_string())
+            .add_attribute("guest", guest_addr.to_string())
+            .add_attribute("claimant", info.sender.to_string()))
+    }
+
+    pub fn claim_result(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        host_addr: &Addr,
+        guest_addr: &Addr,
+    ) -> Result<Response, ContractError> {
+        let mut games = GAMES.load(deps.storage, (host_addr, guest_addr))?;
+
+        let no_game = no_game_in_progress(host_addr, guest_addr, &games);
+        let game = games.current.as_ref().ok_or(no_game)?;
+
+        player_role(games.host, host_addr, guest_addr, &info.sender)?;
+        ensure!(
+            game.is_over(),
+            ContractError::GameNotOver {
+                host: host_addr.to_string(),
+                guest: guest_addr.to_string(),
+            }
+        );
+        let result = game.result();
+
+        let events = complete_if_over(deps.storage, &env, host_addr, guest_addr, &mut games)?;
+
+        GAMES.save(deps.storage, (host_addr, guest_addr), &games)?;
+
+        let mut response = Response::default()
+            .add_events(events)
+            .add_attribute("action", Action::ClaimResult)
+            .add_attribute("host", host_addr.to_string())
+            .add_attribute("guest", guest_addr.to_string())
+            .add_attribute("claimant", info.sender.to_string());
+        if let GameResult::Winner { player, .. } = result {
+            let winner_addr = if player == games.host {
+                host_addr
+            } else {
+                guest_addr
+            };
+            response = response.add_attribute("winner", winner_addr.to_string());
+        }
+        Ok(response)
+    }
+
+    pub fn force_end_game(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        host_addr: &Addr,
+        guest_addr: &Addr,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+        ensure!(info.sender == config.admin, ContractError::Unauthorized);
+
+        let mut games = GAMES.load(deps.storage, (host_addr, guest_addr))?;
+
+        let no_game = no_game_in_progress(host_addr, guest_addr, &games);
+        let game = games.current.as_mut().ok_or(no_game)?;
+        game.abandon(None);
+
+        let events = complete_if_over(deps.storage, &env, host_addr, guest_addr, &mut games)?;
+        // In a match, the next game started right away: the admin ends the whole match, without a winner.
+        if games.current.take().is_some() {
+            update_active_games(deps.storage, host_addr, guest_addr, false)?;
+        }
+
+        GAMES.save(deps.storage, (host_addr, guest_addr), &games)?;
+
+        Ok(Response::default()
+            .add_events(events)
+            .add_attribute("action", Action::ForceEnd)
+            .add_attribute("host", host_addr.to_string())
+            .add_attribute("guest", guest_addr.to_string()))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn import_game(
+        deps: DepsMut,
+        info: MessageInfo,
+        host_addr: &Addr,
+        guest_addr: &Addr,
+        host_role: Player,
+        current: Option<Game>,
+        completed: Vec<Game>,
+        force: bool,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+        ensure!(info.sender == config.admin, ContractError::Unauthorized);
+
+        ensure!(host_role != Player::None, GameError::InvalidPlayer);
+        let mut games = Games {
+            current,
+            completed,
+            ..Games::new(host_role)
+        };
+        if let Some(game) = &games.current {
+            validate_imported_game(game, false)?;
+        }
+        for game in &games.completed {
+            validate_imported_game(game, true)?;
+        }
+
+        match GAMES.may_load(deps.storage, (host_addr, guest_addr))? {
+            Some(existing) => {
+                ensure!(
+                    force || existing.current.is_none(),
+                    ContractError::GameInProgress {
+                        host: host_addr.to_string(),
+                        guest: guest_addr.to_string(),
+                    }
+                );
+                if existing.current.is_some() {
+                    update_active_games(deps.storage, host_addr, guest_addr, false)?;
+                }
+                // The nonces and the number of games of the pair only grow, an import can't reuse them.
+                games.host_nonce = existing.host_nonce;
+                games.guest_nonce = existing.guest_nonce;
+                games.total_completed = existing.completed_count();
+            }
+            None => {
+                TOTAL_PAIRS.update(deps.storage, |total| -> StdResult<_> { Ok(total + 1) })?;
+            }
+        }
+        if games.current.is_some() {
+            update_active_games(deps.storage, host_addr, guest_addr, true)?;
+        }
+        // The global statistics only count the games completed in this contract.
+        GAMES.save(deps.storage, (host_addr, guest_addr), &games)?;
+
+        Ok(Response::default()
+            .add_attribute("action", Action::ImportGame)
+            .add_attribute("host", host_addr.to_string())
+            .add_attribute("guest", guest_addr.to_string())
+            .add_attribute("completed", games.completed.len().to_string()))
+    }
+
+    /// Ensures that an imported game could have been played, and that it is over if it was completed.
+    ///
+    /// A game in progress may be over already, its result is then claimed with `ClaimResult`.
+    fn validate_imported_game(game: &Game, completed: bool) -> Result<(), ContractError> {
+        let replayed = Game::from_board(game.board)?;
+        ensure!(
+            game.turn == replayed.turn,
+            ContractError::InvalidImport {
+                reason: "the turn does not match the board".to_string()
+            }
+        );
+        ensure!(
+            !completed || game.is_over(),
+            ContractError::InvalidImport {
+                reason: "a completed game is not over".to_string()
+            }
+        );
+        ensure!(
+            game.host_role != Some(Player::None),
+            GameError::InvalidPlayer
+        );
+        Ok(())
+    }
+
+    pub fn purge_pair(
+        deps: DepsMut,
+        info: MessageInfo,
+        host_addr: &Addr,
+        guest_addr: &Addr,
+        force: bool,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+        ensure!(info.sender == config.admin, ContractError::Unauthorized);
+
+        let games = GAMES.load(deps.storage, (host_addr, guest_addr))?;
+        ensure!(
+            force || games.current.is_none(),
+            ContractError::GameInProgress {
+                host: host_addr.to_string(),
+                guest: guest_addr.to_string(),
+            }
+        );
+
+        GAMES.remove(deps.storage, (host_addr, guest_addr));
+        if games.current.is_some() {
+            update_active_games(deps.storage, host_addr, guest_addr, false)?;
+        }
+        TOTAL_PAIRS.update(deps.storage, |total| -> StdResult<_> {
+            Ok(total.saturating_sub(1))
+        })?;
+
+        Ok(Response::default()
+            .add_attribute("action", Action::PurgePair)
+            .add_attribute("host", host_addr.to_string())
+            .add_attribute("guest", guest_addr.to_string()))
+    }
+
+    pub fn set_nickname(
+        deps: DepsMut,
+        info: MessageInfo,
+        nickname: String,
+    ) -> Result<Response, ContractError> {
+        ensure!(
+            !nickname.trim().is_empty() && nickname.chars().count() <= MAX_NICKNAME_LENGTH,
+            ContractError::InvalidNickname {
+                max: MAX_NICKNAME_LENGTH
+            }
+        );
+
+        NICKNAMES.save(deps.storage, &info.sender, &nickname)?;
+
+        Ok(Response::default()
+            .add_attribute("action", Action::SetNickname)
+            .add_attribute("player", info.sender.to_string())
+            .add_attribute("nickname", nickname))
+    }
+
+    pub fn post_message(
+        deps: DepsMut,
+        info: MessageInfo,
+        host_addr: &Addr,
+        guest_addr: &Addr,
+        text: String,
+    ) -> Result<Response, ContractError> {
+        ensure!(
+            info.sender == *host_addr || info.sender == *guest_addr,
+            ContractError::NotInvolved {
+                host: host_addr.to_string(),
+                guest: guest_addr.to_string(),
+                player: info.sender.to_string(),
+            }
+        );
+        ensure!(
+            !text.trim().is_empty() && text.chars().count() <= MAX_MESSAGE_LENGTH,
+            ContractError::InvalidMessage {
+                max: MAX_MESSAGE_LENGTH
+            }
+        );
+
+        let mut games = GAMES.load(deps.storage, (host_addr, guest_addr))?;
+        if games.chat.len() >= MAX_CHAT_MESSAGES {
+            games.chat.remove(0);
+        }
+        games.chat.push((info.sender.clone(), text));
+        GAMES.save(deps.storage, (host_addr, guest_addr), &games)?;
+
+        Ok(Response::default()
+            .add_attribute("action", Action::PostMessage)
+            .add_attribute("host", host_addr.to_string())
+            .add_attribute("guest", guest_addr.to_string())
+            .add_attribute("sender", info.sender.to_string()))
+    }
+
+    pub fn clear_my_history(
+        deps: DepsMut,
+        info: MessageInfo,
+        host_addr: &Addr,
+        guest_addr: &Addr,
+    ) -> Result<Response, ContractError> {
+        ensure!(
+            info.sender == *host_addr || info.sender == *guest_addr,
+            ContractError::NotInvolved {
+                host: host_addr.to_string(),
+                guest: guest_addr.to_string(),
+                player: info.sender.to_string(),
+            }
+        );
+        let mut games = GAMES.load(deps.storage, (host_addr, guest_addr))?;
+
+        // The history is shared, it is only deleted once the opponent confirms the request.
+        let cleared = games
+            .clear_requested_by
+            .as_ref()
+            .is_some_and(|requester| requester != info.sender);
+        if cleared {
+            games.completed.clear();
+            games.clear_requested_by = None;
+        } else {
+            games.clear_requested_by = Some(info.sender.clone());
+        }
+
+        GAMES.save(deps.storage, (host_addr, guest_addr), &games)?;
+
+        Ok(Response::default()
+            .add_attribute("action", Action::ClearHistory)
+            .add_attribute("host", host_addr.to_string())
+            .add_attribute("guest", guest_addr.to_string())
+            .add_attribute("sender", info.sender.to_string())
+            .add_attribute("cleared", cleared.to_string()))
+    }
+
+    pub fn update_config(
+        deps: DepsMut,
+        info: MessageInfo,
+        invite_timeout: Option<u64>,
+        move_timeout: Option<u64>,
+    ) -> Result<Response, ContractError> {
+        let mut config = CONFIG.load(deps.storage)?;
+        ensure!(info.sender == config.admin, ContractError::Unauthorized);
+
+        if let Some(invite_timeout) = invite_timeout {
+            config.invite_timeout = invite_timeout;
+        }
+        if let Some(move_timeout) = move_timeout {
+            config.move_timeout = move_timeout;
+        }
+        validate_config(&config)?;
+
+        CONFIG.save(deps.storage, &config)?;
+
+        Ok(Response::default()
+            .add_attribute("action", Action::UpdateConfig)
+            .add_attribute("invite_timeout", config.invite_timeout.to_string())
+            .add_attribute("move_timeout", config.move_timeout.to_string()))
+    }
+
+    /// The outcome of a move played with `play_move`.
+    enum MoveOutcome {
+        /// The move was played.
+        Played,
+        /// The move was illegal and counted against the player, who forfeited the game past the limit.
+        Illegal {
+            reason: GameError,
+            count: u32,
+            forfeit: bool,
+        },
+    }
+
+    /// Plays a move of the player in the current game, for both `Play` and `PlaySequence`.
+    ///
+    /// The nonce, if any, must be greater than the last one sent by the player. A legal move is charged to the time
+    /// bank of the player and resets their count of illegal moves. An illegal move is rejected, unless the
+    /// configuration sets a limit of illegal moves: it is then counted, and the player forfeits the game past the
+    /// limit.
+    fn play_move(
+        storage: &dyn Storage,
+        games: &mut Games,
+        player: Player,
+        cell: usize,
+        nonce: Option<u64>,
+        now: Timestamp,
+    ) -> Result<MoveOutcome, ContractError> {
+        let is_host = player == games.host;
+        // Callers ensure a game is in progress, a missing one is over anyway.
+        let game = games.current.as_mut().ok_or(GameError::GameOver)?;
+
+        if let Some(nonce) = nonce {
+            let last_nonce = if is_host {
+                &mut games.host_nonce
+            } else {
+                &mut games.guest_nonce
+            };
+            ensure!(
+                last_nonce.is_none_or(|last| nonce > last),
+                ContractError::DuplicateNonce { nonce }
+            );
+            *last_nonce = Some(nonce);
+        }
+        let illegal_moves = if is_host {
+            &mut games.host_illegal_moves
+        } else {
+            &mut games.guest_illegal_moves
+        };
+
+        if let Err(reason) = game.play(player, cell) {
+            // Without a limit, illegal moves are rejected. Otherwise they are counted, which requires the
+            // transaction to succeed, and the player forfeits the game past the limit.
+            let max_illegal_moves = match CONFIG.load(storage)?.max_illegal_moves {
+                Some(max_illegal_moves) => max_illegal_moves,
+                None => return Err(reason.into()),
+            };
+            *illegal_moves += 1;
+            let count = *illegal_moves;
+            let forfeit = count > max_illegal_moves;
+            if forfeit {
+                game.abandon(Some(player));
+            }
+            return Ok(MoveOutcome::Illegal {
+                reason,
+                count,
+                forfeit,
+            });
+        }
+        *illegal_moves = 0;
+        if games.time_bank.is_some() {
+            let time_left = if is_host {
+                &mut games.host_time_left
+            } else {
+                &mut games.guest_time_left
+            };
+            charge_time_bank(game, time_left, now)?;
+        }
+        game.last_move_at = Some(now);
+        Ok(MoveOutcome::Played)
+    }
+
+    /// Charges the time elapsed since the last move of the game to a time bank. Returns `ContractError::OutOfTime`
+    /// if the bank ran out before the move.
+    fn charge_time_bank(
+        game: &Game,
+        time_left: &mut u64,
+        now: Timestamp,
+    ) -> Result<(), ContractError> {
+        let since = game.last_move_at.or(game.created_at).unwrap_or(now);
+        let elapsed = now.seconds().saturating_sub(since.seconds());
+        ensure!(elapsed < *time_left, ContractError::OutOfTime);
+        *time_left -= elapsed;
+        Ok(())
+    }
+
+    /// Fills the time banks of both players for a new game. Does nothing if the players don't use a time bank.
+    fn reset_time_banks(games: &mut Games) {
+        if let Some(time_bank) = games.time_bank {
+            games.host_time_left = time_bank;
+            games.guest_time_left = time_bank;
+        }
+    }
+
+    /// Starts the given game, answering the pending invitation.
+    fn start_game(
+        storage: &mut dyn Storage,
+        env: &Env,
+        host_addr: &Addr,
+        guest_addr: &Addr,
+        games: &mut Games,
+        game: Game,
+    ) -> StdResult<()> {
+        update_active_games(storage, host_addr, guest_addr, true)?;
+        reset_time_banks(games);
+        games.pending_invition = false;
+        games.invited_at = None;
+        games.invite_message = None;
+        games.current = Some(Game {
+            created_at: Some(env.block.time),
+            ..game
+        });
+        Ok(())
+    }
+
+    /// Extends the win streak of a player who won a game, or ends it otherwise, draws included.
+    fn record_streak(storage: &mut dyn Storage, player_addr: &Addr, won: bool) -> StdResult<()> {
+        PLAYER_STATS.update(storage, player_addr, |stats| -> StdResult<_> {
+            let mut stats = stats.unwrap_or_default();
+            if won {
+                stats.current_streak += 1;
+                stats.longest_streak = stats.longest_streak.max(stats.current_streak);
+            } else {
+                stats.current_streak = 0;
+            }
+            Ok(stats)
+        })?;
+        Ok(())
+    }
+
+    /// Moves the current game to the completed games if it is over.
+    ///
+    /// Returns the `game_over` event describing the outcome of the game. In a match, the winner of the game scores a
+    /// point and the next game starts right away, until a player reaches the match target. The `match_over` event is
+    /// returned too when the match ends.
+    fn complete_if_over(
+        storage: &mut dyn Storage,
+        env: &Env,
+        host_addr: &Addr,
+        guest_addr: &Addr,
+        games: &mut Games,
+    ) -> StdResult<Vec<Event>> {
+        let mut game = match games.current.filter(|game| game.is_over()) {
+            Some(game) => game,
+            None => return Ok(vec![]),
+        };
+        game.completed_at = Some(env.block.time);
+        game.host_role = Some(games.host);
+        games.total_completed = games.completed_count() + 1;
+        if CONFIG.load(storage)?.store_history {
+            games.completed.push(game);
+        }
+        games.current = None;
+        games.host_illegal_moves = 0;
+        games.guest_illegal_moves = 0;
+        reset_time_banks(games);
+        TOTAL_COMPLETED.update(storage, |total| -> StdResult<_> { Ok(total + 1) })?;
+        let result = game.result();
+        // Abandoned games are not draws, even without a winner.
+        if result == GameResult::Draw {
+            TOTAL_DRAWS.update(storage, |total| -> StdResult<_> { Ok(total + 1) })?;
+        }
+
+        let winner = result.winner();
+        record_streak(storage, host_addr, winner == Some(games.host))?;
+        record_streak(storage, guest_addr, winner == Some(games.host.opponent()))?;
+
+        let mut game_over = Event::new("game_over")
+            .add_attribute("action", Action::GameOver)
+            .add_attribute("host", host_addr.to_string())
+            .add_attribute("guest", guest_addr.to_string())
+            .add_attribute("move_count", game.move_count().to_string());
+        game_over = match winner {
+            Some(winner) => {
+                let winner_addr = if winner == games.host {
+                    host_addr
+                } else {
+                    guest_addr
+                };
+                game_over
+                    .add_attribute("winner_symbol", winner.to_string())
+                    .add_attribute("winner_address", winner_addr.to_string())
+            }
+            None => game_over.add_attribute("winner_symbol", "none"),
+        };
+        let mut events = vec![game_over];
+
+        if games.match_target.is_none() {
+            update_active_games(storage, host_addr, guest_addr, false)?;
+            return Ok(events);
+        }
+
+        if winner == Some(games.host) {
+            games.host_score += 1;
+        } else if winner.is_some() {
+            games.guest_score += 1;
+        }
+
+        match games.match_winner() {
+            Some(winner) => {
+                update_active_games(storage, host_addr, guest_addr, false)?;
+                let winner_addr = if winner == games.host {
+                    host_addr
+                } else {
+                    guest_addr
+                };
+                events.push(
+                    Event::new("match_over")
+                        .add_attribute("action", Action::MatchOver)
+                        .add_attribute("host", host_addr.to_string())
+                        .add_attribute("guest", guest_addr.to_string())
+                        .add_attribute("winner", winner_addr.to_string())
+                        .add_attribute("host_score", games.host_score.to_string())
+                        .add_attribute("guest_score", games.guest_score.to_string()),
+                );
+            }
+            None => {
+                games.current = Some(Game {
+                    created_at: Some(env.block.time),
+                    ..Game::new()
+                });
+            }
+        }
+        Ok(events)
+    }
+
+    /// Get the host role based on the hash of the inviter and guest addresses.
+    ///
+    /// The addresses are sorted before being hashed, so that each player gets the same symbol whoever invited. The
+    /// first bit of the hash is used to determine the symbol of the highest address. If the first bit is 0, it plays
+    /// O, otherwise it plays X.
+    fn get_host_role(host_addr: &Addr, guest_addr: &Addr) -> Player {
+        let (low, high) = if host_addr <= guest_addr {
+            (host_addr, guest_addr)
+        } else {
+            (guest_addr, host_addr)
+        };
+        let concat = format!("{low}{high}");
+        let mut hasher = DefaultHasher::new();
+        concat.hash(&mut hasher);
+        let hash = hasher.finish().to_string();
+
+        let first_bit = hash.as_bytes()[0] & 1;
+        let high_role = if first_bit == 0 { Player::O } else { Player::X };
+        if host_addr == high {
+            high_role
+        } else {
+            high_role.opponent()
+        }
+    }
+}
+
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
+    match msg {
+        QueryMsg::Config {} => Ok(to_json_binary(&CONFIG.load(deps.storage)?)?),
+        QueryMsg::ContractInfo {} => Ok(to_json_binary(&cw2::get_contract_version(deps.storage)?)?),
+        QueryMsg::TotalGames {} => Ok(to_json_binary(&query::total_games(deps)?)?),
+        QueryMsg::GamesCount {} => Ok(to_json_binary(&query::games_count(deps)?)?),
+        QueryMsg::GlobalStats {} => Ok(to_json_binary(&query::global_stats(deps)?)?),
+        QueryMsg::Games { host, guest } => {
+            let host_addr = deps.api.addr_validate(&host)?;
+            let guest_addr = deps.api.addr_validate(&guest)?;
+            Ok(to_json_binary(&query::games(
+                deps,
+                &host_addr,
+                &guest_addr,
+            )?)?)
+        }
+        QueryMsg::PairExists { host, guest } => {
+            let host_addr = deps.api.addr_validate(&host)?;
+            let guest_addr = deps.api.addr_validate(&guest)?;
+            Ok(to_json_binary(&query::pair_exists(
+                deps,
+                &host_addr,
+                &guest_addr,
+            )?)?)
+        }
+        QueryMsg::GamesBatch { pairs } => {
+            ensure!(
+                pairs.len() <= MAX_BATCH_SIZE,
+                ContractError::BatchTooLarge {
+                    max: MAX_BATCH_SIZE
+                }
+            );
+            let pairs = pairs
+                .iter()
+                .map(|(host, guest)| {
+                    Ok((
+                        deps.api.addr_validate(host)?,
+                        deps.api.addr_validate(guest)?,
+                    ))
+                })
+                .collect::<StdResult<Vec<_>>>()?;
+            Ok(to_json_binary(&query::games_batch(deps, &pairs)?)?)
+        }
+        QueryMsg::StorageKey { host, guest } => {
+            let host_addr = deps.api.addr_validate(&host)?;
+            let guest_addr = deps.api.addr_validate(&guest)?;
+            Ok(to_json_binary(&query::storage_key(
+                deps,
+                &host_addr,
+                &guest_addr,
+            )?)?)
+        }
+        QueryMsg::AllGamesList {
+            descending,
+            from,
+            to,
+            min_completed,
+            start_after,
+            limit,
+        } => {
+            let order = if descending.unwrap_or(false) {
+                Order::Descending
             } else {
-                Player::O
+                Order::Ascending
+            };
+            let start_after = validate_cursor(deps.api, start_after)?;
+            let limit = limit.map(|limit| limit.min(MAX_PAIRS_LIMIT) as usize);
+            Ok(to_json_binary(&query::all_games_list(
+                deps,
+                order,
+                from,
+                to,
+                min_completed.unwrap_or(0),
+                start_after,
+                limit,
+            )?)?)
+        }
+        QueryMsg::AllPairs { start_after, limit } => {
+            let start_after = validate_cursor(deps.api, start_after)?;
+            let limit = limit.unwrap_or(DEFAULT_PAIRS_LIMIT).min(MAX_PAIRS_LIMIT) as usize;
+            Ok(to_json_binary(&query::all_pairs(
+                deps,
+                start_after,
+                limit,
+            )?)?)
+        }
+        QueryMsg::Winner { host, guest } => {
+            let host_addr = deps.api.addr_validate(&host)?;
+            let guest_addr = deps.api.addr_validate(&guest)?;
+            Ok(to_json_binary(&query::winner(
+                deps,
+                &host_addr,
+                &guest_addr,
+            )?)?)
+        }
+        QueryMsg::LastGame { host, guest } => {
+            let host_addr = deps.api.addr_validate(&host)?;
+            let guest_addr = deps.api.addr_validate(&guest)?;
+            Ok(to_json_binary(&query::last_game(
+                deps,
+                &host_addr,
+                &guest_addr,
+            )?)?)
+        }
+        QueryMsg::CompletedGame { host, guest, index } => {
+            let host_addr = deps.api.addr_validate(&host)?;
+            let guest_addr = deps.api.addr_validate(&guest)?;
+            Ok(to_json_binary(&query::completed_game(
+                deps,
+                &host_addr,
+                &guest_addr,
+                index,
+            )?)?)
+        }
+        QueryMsg::BoardRows { host, guest } => {
+            let host_addr = deps.api.addr_validate(&host)?;
+            let guest_addr = deps.api.addr_validate(&guest)?;
+            Ok(to_json_binary(&query::board_rows(
+                deps,
+                &host_addr,
+                &guest_addr,
+            )?)?)
+        }
+        QueryMsg::SuggestMove { host, guest } => {
+            let host_addr = deps.api.addr_validate(&host)?;
+            let guest_addr = deps.api.addr_validate(&guest)?;
+            Ok(to_json_binary(&query::suggest_move(
+                deps,
+                &host_addr,
+                &guest_addr,
+            )?)?)
+        }
+        QueryMsg::IsMyTurn {
+            host,
+            guest,
+            player,
+        } => {
+            let host_addr = deps.api.addr_validate(&host)?;
+            let guest_addr = deps.api.addr_validate(&guest)?;
+            let player_addr = deps.api.addr_validate(&player)?;
+            Ok(to_json_binary(&query::is_my_turn(
+                deps,
+                &host_addr,
+                &guest_addr,
+                &player_addr,
+            )?)?)
+        }
+        QueryMsg::Dashboard { player } => {
+            let player_addr = deps.api.addr_validate(&player)?;
+            Ok(to_json_binary(&query::dashboard(deps, &player_addr)?)?)
+        }
+        QueryMsg::ActiveOpponents { player } => {
+            let player_addr = deps.api.addr_validate(&player)?;
+            Ok(to_json_binary(&query::active_opponents(
+                deps,
+                &player_addr,
+            )?)?)
+        }
+        QueryMsg::PendingInvitesFor { guest } => {
+            let guest_addr = deps.api.addr_validate(&guest)?;
+            Ok(to_json_binary(&query::pending_invites_for(
+                deps,
+                &guest_addr,
+            )?)?)
+        }
+        QueryMsg::GamesAsGuest { guest } => {
+            let guest_addr = deps.api.addr_validate(&guest)?;
+            Ok(to_json_binary(&query::games_as_guest(deps, &guest_addr)?)?)
+        }
+        QueryMsg::GamesByStatus { status } => {
+            Ok(to_json_binary(&query::games_by_status(deps, status)?)?)
+        }
+        QueryMsg::GamesByResult { result } => {
+            Ok(to_json_binary(&query::games_by_result(deps, result)?)?)
+        }
+        QueryMsg::EvaluatePosition { host, guest } => {
+            let host_addr = deps.api.addr_validate(&host)?;
+            let guest_addr = deps.api.addr_validate(&guest)?;
+            Ok(to_json_binary(&query::evaluate_position(
+                deps,
+                &host_addr,
+                &guest_addr,
+            )?)?)
+        }
+        QueryMsg::Stats { player } => {
+            let player_addr = deps.api.addr_validate(&player)?;
+            Ok(to_json_binary(&query::stats(deps, &player_addr)?)?)
+        }
+        QueryMsg::Nickname { player } => {
+            let player_addr = deps.api.addr_validate(&player)?;
+            Ok(to_json_binary(&query::nickname(deps, &player_addr)?)?)
+        }
+        QueryMsg::Chat { host, guest } => {
+            let host_addr = deps.api.addr_validate(&host)?;
+            let guest_addr = deps.api.addr_validate(&guest)?;
+            Ok(to_json_binary(&query::chat(
+                deps,
+                &host_addr,
+                &guest_addr,
+            )?)?)
+        }
+        QueryMsg::Odds { host, guest } => {
+            let host_addr = deps.api.addr_validate(&host)?;
+            let guest_addr = deps.api.addr_validate(&guest)?;
+            Ok(to_json_binary(&query::odds(
+                deps,
+                &host_addr,
+                &guest_addr,
+            )?)?)
+        }
+        QueryMsg::Threats {
+            host,
+            guest,
+            player,
+        } => {
+            let host_addr = deps.api.addr_validate(&host)?;
+            let guest_addr = deps.api.addr_validate(&guest)?;
+            Ok(to_json_binary(&query::threats(
+                deps,
+                &host_addr,
+                &guest_addr,
+                player,
+            )?)?)
+        }
+        QueryMsg::PackedBoard { host, guest } => {
+            let host_addr = deps.api.addr_validate(&host)?;
+            let guest_addr = deps.api.addr_validate(&guest)?;
+            Ok(to_json_binary(&query::packed_board(
+                deps,
+                &host_addr,
+                &guest_addr,
+            )?)?)
+        }
+        QueryMsg::EmptyCount { host, guest } => {
+            let host_addr = deps.api.addr_validate(&host)?;
+            let guest_addr = deps.api.addr_validate(&guest)?;
+            Ok(to_json_binary(&query::empty_count(
+                deps,
+                &host_addr,
+                &guest_addr,
+            )?)?)
+        }
+        QueryMsg::Hint { host, guest } => {
+            let host_addr = deps.api.addr_validate(&host)?;
+            let guest_addr = deps.api.addr_validate(&guest)?;
+            Ok(to_json_binary(&query::hint(
+                deps,
+                &host_addr,
+                &guest_addr,
+            )?)?)
+        }
+        QueryMsg::NextPlayer { host, guest } => {
+            let host_addr = deps.api.addr_validate(&host)?;
+            let guest_addr = deps.api.addr_validate(&guest)?;
+            Ok(to_json_binary(&query::next_player(
+                deps,
+                &host_addr,
+                &guest_addr,
+            )?)?)
+        }
+        QueryMsg::MySymbol {
+            host,
+            guest,
+            player,
+        } => {
+            let host_addr = deps.api.addr_validate(&host)?;
+            let guest_addr = deps.api.addr_validate(&guest)?;
+            let player_addr = deps.api.addr_validate(&player)?;
+            Ok(to_json_binary(&query::my_symbol(
+                deps,
+                &host_addr,
+                &guest_addr,
+                &player_addr,
+            )?)?)
+        }
+        QueryMsg::CanPlay {
+            host,
+            guest,
+            player,
+            cell,
+        } => {
+            let host_addr = deps.api.addr_validate(&host)?;
+            let guest_addr = deps.api.addr_validate(&guest)?;
+            let player_addr = deps.api.addr_validate(&player)?;
+            Ok(to_json_binary(&query::can_play(
+                deps,
+                &host_addr,
+                &guest_addr,
+                &player_addr,
+                cell,
+            )?)?)
+        }
+        QueryMsg::MovePreview { host, guest, cell } => {
+            let host_addr = deps.api.addr_validate(&host)?;
+            let guest_addr = deps.api.addr_validate(&guest)?;
+            Ok(to_json_binary(&query::move_preview(
+                deps,
+                &host_addr,
+                &guest_addr,
+                cell,
+            )?)?)
+        }
+        QueryMsg::ValidateGame { host, guest } => {
+            let host_addr = deps.api.addr_validate(&host)?;
+            let guest_addr = deps.api.addr_validate(&guest)?;
+            Ok(to_json_binary(&query::validate_game(
+                deps,
+                &host_addr,
+                &guest_addr,
+            )?)?)
+        }
+        QueryMsg::MatchStatus { host, guest } => {
+            let host_addr = deps.api.addr_validate(&host)?;
+            let guest_addr = deps.api.addr_validate(&guest)?;
+            Ok(to_json_binary(&query::match_status(
+                deps,
+                &host_addr,
+                &guest_addr,
+            )?)?)
+        }
+    }
+}
+
+mod query {
+    use super::*;
+    use crate::{
+        game::GameResult,
+        msg::{
+            AllGamesListResponse, AllPairsResponse, CanPlayResponse, CompletedGameInfo,
+            CompletedGameResponse, DashboardResponse, EvaluatePositionResponse, GamesCountResponse,
+            GamesInfo, GamesResponse, GlobalStatsResponse, LastGameResponse, MatchStatusResponse,
+            MovePreviewResponse, NextPlayerResponse, NicknameResponse, OddsResponse,
+            PendingInvitesResponse, ResultFilter, SuggestMoveResponse, TotalGamesResponse,
+            ValidateGameResponse,
+        },
+        state::{GameStatus, Games, PlayerStats},
+    };
+
+    /// The maximum number of results returned in the dashboard.
+    const RECENT_RESULTS_LIMIT: usize = 10;
+
+    pub fn games(
+        deps: Deps,
+        host_addr: &Addr,
+        guest_addr: &Addr,
+    ) -> Result<GamesResponse, ContractError> {
+        let games = GAMES.load(deps.storage, (host_addr, guest_addr))?;
+
+        let game_info = games_info(deps.storage, host_addr, guest_addr, games)?;
+        Ok(GamesResponse { info: game_info })
+    }
+
+    pub fn pair_exists(
+        deps: Deps,
+        host_addr: &Addr,
+        guest_addr: &Addr,
+    ) -> Result<bool, ContractError> {
+        Ok(GAMES.has(deps.storage, (host_addr, guest_addr)))
+    }
+
+    pub fn games_batch(
+        deps: Deps,
+        pairs: &[(Addr, Addr)],
+    ) -> Result<Vec<Option<GamesInfo>>, ContractError> {
+        let infos: StdResult<Vec<_>> = pairs
+            .iter()
+            .map(|(host_addr, guest_addr)| {
+                GAMES
+                    .may_load(deps.storage, (host_addr, guest_addr))?
+                    .map(|games| games_info(deps.storage, host_addr, guest_addr, games))
+                    .transpose()
+            })
+            .collect();
+
+        Ok(infos?)
+    }
+
+    pub fn storage_key(
+        deps: Deps,
+        host_addr: &Addr,
+        guest_addr: &Addr,
+    ) -> Result<String, ContractError> {
+        // The games of a pair are stored under the host and guest of the first invitation.
+        let reversed = !GAMES.has(deps.storage, (host_addr, guest_addr))
+            && GAMES.has(deps.storage, (guest_addr, host_addr));
+        let key = if reversed {
+            GAMES.key((guest_addr, host_addr))
+        } else {
+            GAMES.key((host_addr, guest_addr))
+        };
+        Ok(key.iter().map(|byte| format!("{byte:02x}")).collect())
+    }
+
+    pub fn all_pairs(
+        deps: Deps,
+        start_after: Option<(Addr, Addr)>,
+        limit: usize,
+    ) -> Result<AllPairsResponse, ContractError> {
+        let start = start_after
+            .as_ref()
+            .map(|(host_addr, guest_addr)| Bound::exclusive((host_addr, guest_addr)));
+        let pairs = GAMES
+            .keys(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|key| key.map(|(host, guest)| (host.into_string(), guest.into_string())))
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(AllPairsResponse {
+            next_cursor: next_cursor(&pairs, Some(limit)),
+            pairs,
+        })
+    }
+
+    pub fn all_games_list(
+        deps: Deps,
+        order: Order,
+        from: Option<Timestamp>,
+        to: Option<Timestamp>,
+        min_completed: u32,
+        start_after: Option<(Addr, Addr)>,
+        limit: Option<usize>,
+    ) -> Result<AllGamesListResponse, ContractError> {
+        let in_window = |time: Option<Timestamp>| match time {
+            Some(time) => from.is_none_or(|from| time >= from) && to.is_none_or(|to| time <= to),
+            None => false,
+        };
+
+        // The page starts after the cursor in the order of the listing.
+        let cursor = start_after
+            .as_ref()
+            .map(|(host_addr, guest_addr)| Bound::exclusive((host_addr, guest_addr)));
+        let (min, max) = match order {
+            Order::Ascending => (cursor, None),
+            Order::Descending => (None, cursor),
+        };
+
+        let mut games = vec![];
+        let mut pairs = vec![];
+        for game in GAMES.range(deps.storage, min, max, order) {
+            if limit.is_some_and(|limit| games.len() >= limit) {
+                break;
+            }
+            let ((host, guest), value) = game?;
+            // Without a window, pairs without any game are listed too.
+            let listed = (from.is_none() && to.is_none())
+                || value
+                    .completed
+                    .iter()
+                    .chain(value.current.iter())
+                    .any(|game| in_window(game.created_at) || in_window(game.completed_at));
+            if listed && value.completed.len() >= min_completed as usize {
+                pairs.push((host.to_string(), guest.to_string()));
+                games.push(games_info(deps.storage, &host, &guest, value)?);
+            }
+        }
+
+        Ok(AllGamesListResponse {
+            games,
+            next_cursor: next_cursor(&pairs, limit),
+        })
+    }
+
+    pub fn total_games(deps: Deps) -> Result<TotalGamesResponse, ContractError> {
+        let total_completed = TOTAL_COMPLETED.load(deps.storage)?;
+        Ok(TotalGamesResponse { total_completed })
+    }
+
+    pub fn global_stats(deps: Deps) -> Result<GlobalStatsResponse, ContractError> {
+        Ok(GlobalStatsResponse {
+            total_completed: TOTAL_COMPLETED.load(deps.storage)?,
+            total_draws: TOTAL_DRAWS.load(deps.storage)?,
+        })
+    }
+
+    pub fn games_count(deps: Deps) -> Result<GamesCountResponse, ContractError> {
+        let count = TOTAL_PAIRS.load(deps.storage)?;
+        Ok(GamesCountResponse { count })
+    }
+
+    pub fn last_game(
+        deps: Deps,
+        host_addr: &Addr,
+        guest_addr: &Addr,
+    ) -> Result<LastGameResponse, ContractError> {
+        let last = GAMES
+            .may_load(deps.storage, (host_addr, guest_addr))?
+            .and_then(|games| games.completed.last().copied());
+
+        Ok(LastGameResponse {
+            game: last,
+            result: last.map(|game| game.result()),
+        })
+    }
+
+    pub fn winner(
+        deps: Deps,
+        host_addr: &Addr,
+        guest_addr: &Addr,
+    ) -> Result<Option<String>, ContractError> {
+        let games = GAMES.load(deps.storage, (host_addr, guest_addr))?;
+
+        let game = games.current.or_else(|| games.completed.last().copied());
+        Ok(game.and_then(|game| winner_addr(&game, games.host, host_addr, guest_addr)))
+    }
+
+    pub fn completed_game(
+        deps: Deps,
+        host_addr: &Addr,
+        guest_addr: &Addr,
+        index: usize,
+    ) -> Result<CompletedGameResponse, ContractError> {
+        let games = GAMES.load(deps.storage, (host_addr, guest_addr))?;
+
+        let game =
+            games
+                .completed
+                .get(index)
+                .copied()
+                .ok_or(ContractError::GameIndexOutOfRange {
+                    index,
+                    completed: games.completed.len(),
+                })?;
+
+        Ok(CompletedGameResponse {
+            game,
+            result: game.result(),
+        })
+    }
+
+    pub fn board_rows(
+        deps: Deps,
+        host_addr: &Addr,
+        guest_addr: &Addr,
+    ) -> Result<Vec<Vec<Player>>, ContractError> {
+        let games = GAMES.load(deps.storage, (host_addr, guest_addr))?;
+
+        let no_game = no_game_in_progress(host_addr, guest_addr, &games);
+        let game = games.current.ok_or(no_game)?;
+
+        Ok(game.rows())
+    }
+
+    pub fn suggest_move(
+        deps: Deps,
+        host_addr: &Addr,
+        guest_addr: &Addr,
+    ) -> Result<SuggestMoveResponse, ContractError> {
+        let games = GAMES.load(deps.storage, (host_addr, guest_addr))?;
+
+        let cell = games.current.and_then(|game| game.best_move()).ok_or(
+            ContractError::NoGameInProgress {
+                host: host_addr.to_string(),
+                guest: guest_addr.to_string(),
             },
-            pending_invitation: games.pending_invition,
-            current_game: games.current,
-            completed_games: games.completed,
+        )?;
+
+        Ok(SuggestMoveResponse { cell })
+    }
+
+    pub fn evaluate_position(
+        deps: Deps,
+        host_addr: &Addr,
+        guest_addr: &Addr,
+    ) -> Result<EvaluatePositionResponse, ContractError> {
+        let games = GAMES.load(deps.storage, (host_addr, guest_addr))?;
+
+        let no_game = no_game_in_progress(host_addr, guest_addr, &games);
+        let game = games.current.ok_or(no_game)?;
+
+        Ok(EvaluatePositionResponse {
+            best_outcome: game.evaluate(),
+            best_move: game.best_move(),
+        })
+    }
+
+    pub fn is_my_turn(
+        deps: Deps,
+        host_addr: &Addr,
+        guest_addr: &Addr,
+        player_addr: &Addr,
+    ) -> Result<bool, ContractError> {
+        let games = match GAMES.may_load(deps.storage, (host_addr, guest_addr))? {
+            Some(games) => games,
+            None => return Ok(false),
         };
-        Ok(GamesResponse { info: game_info })
+        let game = match games.current {
+            Some(game) => game,
+            None => return Ok(false),
+        };
+
+        let is_my_turn = (player_addr == host_addr && game.turn == games.host)
+            || (player_addr == guest_addr && game.turn == games.host.opponent());
+        Ok(is_my_turn)
+    }
+
+    pub fn dashboard(deps: Deps, player_addr: &Addr) -> Result<DashboardResponse, ContractError> {
+        let mut dashboard = DashboardResponse {
+            active: vec![],
+            pending_incoming: vec![],
+            pending_outgoing: vec![],
+            recent_results: vec![],
+        };
+        let mut completed = vec![];
+
+        for game in GAMES.range(deps.storage, None, None, Order::Ascending) {
+            let ((host, guest), games) = game?;
+            if host != *player_addr && guest != *player_addr {
+                continue;
+            }
+
+            if games.pending_invition {
+                if guest == *player_addr {
+                    dashboard.pending_incoming.push(host.to_string());
+                }
+                if host == *player_addr {
+                    dashboard.pending_outgoing.push(guest.to_string());
+                }
+            }
+            completed.extend(games.completed.iter().copied());
+            if games.current.is_some() {
+                let info = games_info(deps.storage, &host, &guest, games)?;
+                dashboard.active.push(info);
+            }
+        }
+
+        // Most recently completed games first.
+        completed.sort_by_key(|game| std::cmp::Reverse(game.completed_at));
+        dashboard.recent_results = completed
+            .iter()
+            .take(RECENT_RESULTS_LIMIT)
+            .map(|game| game.result())
+            .collect();
+
+        Ok(dashboard)
+    }
+
+    pub fn pending_invites_for(
+        deps: Deps,
+        guest_addr: &Addr,
+    ) -> Result<PendingInvitesResponse, ContractError> {
+        let hosts: StdResult<Vec<_>> = GAMES
+            .range(deps.storage, None, None, Order::Ascending)
+            .filter_map(|game| match game {
+                Ok(((host, guest), games)) if guest == *guest_addr && games.pending_invition => {
+                    Some(Ok(host.to_string()))
+                }
+                Ok(_) => None,
+                Err(err) => Some(Err(err)),
+            })
+            .collect();
+
+        Ok(PendingInvitesResponse { hosts: hosts? })
     }
 
-    pub fn all_games_list(deps: Deps) -> Result<AllGamesListResponse, ContractError> {
+    pub fn games_as_guest(
+        deps: Deps,
+        guest_addr: &Addr,
+    ) -> Result<AllGamesListResponse, ContractError> {
+        // The games are keyed by host first, so all the pairs are scanned.
         let games: StdResult<Vec<_>> = GAMES
             .range(deps.storage, None, None, Order::Ascending)
-            .map(|game| {
-                let (key, value) = game?;
-                Ok(GamesInfo {
-                    host: key.0.to_string(),
-                    guest: key.1.to_string(),
-                    host_role: value.host,
-                    guest_role: if value.host == Player::O {
-                        Player::X
+            .filter_map(|game| match game {
+                Ok(((host, guest), games)) if guest == *guest_addr => {
+                    Some(games_info(deps.storage, &host, &guest, games))
+                }
+                Ok(_) => None,
+                Err(err) => Some(Err(err)),
+            })
+            .collect();
+
+        Ok(AllGamesListResponse {
+            games: games?,
+            next_cursor: None,
+        })
+    }
+
+    pub fn active_opponents(deps: Deps, player_addr: &Addr) -> Result<Vec<String>, ContractError> {
+        let opponents: StdResult<Vec<_>> = GAMES
+            .range(deps.storage, None, None, Order::Ascending)
+            .filter_map(|game| match game {
+                Ok(((host, guest), games)) if games.current.is_some() => {
+                    if host == *player_addr {
+                        Some(Ok(guest.to_string()))
+                    } else if guest == *player_addr {
+                        Some(Ok(host.to_string()))
                     } else {
-                        Player::O
-                    },
-                    pending_invitation: value.pending_invition,
-                    current_game: value.current,
-                    completed_games: value.completed,
+                        None
+                    }
+                }
+                Ok(_) => None,
+                Err(err) => Some(Err(err)),
+            })
+            .collect();
+
+        Ok(opponents?)
+    }
+
+    pub fn games_by_status(
+        deps: Deps,
+        status: GameStatus,
+    ) -> Result<AllGamesListResponse, ContractError> {
+        let games: StdResult<Vec<_>> = GAMES
+            .range(deps.storage, None, None, Order::Ascending)
+            .filter(|game| match game {
+                Ok((_, value)) => value.status() == status,
+                Err(_) => true,
+            })
+            .map(|game| {
+                let ((host, guest), value) = game?;
+                games_info(deps.storage, &host, &guest, value)
+            })
+            .collect();
+
+        Ok(AllGamesListResponse {
+            games: games?,
+            next_cursor: None,
+        })
+    }
+
+    pub fn games_by_result(
+        deps: Deps,
+        filter: ResultFilter,
+    ) -> Result<AllGamesListResponse, ContractError> {
+        let matches = |result: &GameResult| filter.matches(result);
+
+        let mut games = vec![];
+        for game in GAMES.range(deps.storage, None, None, Order::Ascending) {
+            let ((host, guest), value) = game?;
+            if !value.completed.iter().any(|game| matches(&game.result())) {
+                continue;
+            }
+            // Filter the completed games of the info rather than the stored ones to keep the game reference.
+            let mut info = games_info(deps.storage, &host, &guest, value)?;
+            (info.completed_games, info.completed_results) = info
+                .completed_games
+                .into_iter()
+                .zip(info.completed_results)
+                .filter(|(_, completed)| matches(&completed.result))
+                .unzip();
+            games.push(info);
+        }
+
+        Ok(AllGamesListResponse {
+            games,
+            next_cursor: None,
+        })
+    }
+
+    pub fn odds(
+        deps: Deps,
+        host_addr: &Addr,
+        guest_addr: &Addr,
+    ) -> Result<OddsResponse, ContractError> {
+        let games = GAMES.load(deps.storage, (host_addr, guest_addr))?;
+
+        let no_game = no_game_in_progress(host_addr, guest_addr, &games);
+        let game = games.current.ok_or(no_game)?;
+
+        let (x_win, o_win, draw) = game.odds();
+        Ok(OddsResponse { x_win, o_win, draw })
+    }
+
+    pub fn threats(
+        deps: Deps,
+        host_addr: &Addr,
+        guest_addr: &Addr,
+        player: Player,
+    ) -> Result<Vec<[usize; 3]>, ContractError> {
+        let games = GAMES.load(deps.storage, (host_addr, guest_addr))?;
+
+        let no_game = no_game_in_progress(host_addr, guest_addr, &games);
+        let game = games.current.ok_or(no_game)?;
+
+        Ok(game.open_lines(player))
+    }
+
+    pub fn packed_board(
+        deps: Deps,
+        host_addr: &Addr,
+        guest_addr: &Addr,
+    ) -> Result<u32, ContractError> {
+        let games = GAMES.load(deps.storage, (host_addr, guest_addr))?;
+
+        let no_game = no_game_in_progress(host_addr, guest_addr, &games);
+        let game = games.current.ok_or(no_game)?;
+        Ok(game.pack())
+    }
+
+    pub fn empty_count(
+        deps: Deps,
+        host_addr: &Addr,
+        guest_addr: &Addr,
+    ) -> Result<usize, ContractError> {
+        let count = GAMES
+            .may_load(deps.storage, (host_addr, guest_addr))?
+            .and_then(|games| games.current)
+            .map_or(0, |game| game.remaining());
+        Ok(count)
+    }
+
+    pub fn hint(
+        deps: Deps,
+        host_addr: &Addr,
+        guest_addr: &Addr,
+    ) -> Result<Option<usize>, ContractError> {
+        let games = GAMES.load(deps.storage, (host_addr, guest_addr))?;
+
+        let no_game = no_game_in_progress(host_addr, guest_addr, &games);
+        let game = games.current.ok_or(no_game)?;
+
+        if game.is_over() {
+            return Ok(None);
+        }
+        // Prefer winning right away, otherwise block the opponent.
+        let hint = game
+            .winning_move(game.turn)
+            .or_else(|| game.winning_move(game.turn.opponent()));
+        Ok(hint)
+    }
+
+    pub fn stats(deps: Deps, player_addr: &Addr) -> Result<PlayerStats, ContractError> {
+        let stats = PLAYER_STATS.may_load(deps.storage, player_addr)?;
+        Ok(stats.unwrap_or_default())
+    }
+
+    pub fn my_symbol(
+        deps: Deps,
+        host_addr: &Addr,
+        guest_addr: &Addr,
+        player_addr: &Addr,
+    ) -> Result<Player, ContractError> {
+        let games = GAMES.load(deps.storage, (host_addr, guest_addr))?;
+        player_role(games.host, host_addr, guest_addr, player_addr)
+    }
+
+    pub fn next_player(
+        deps: Deps,
+        host_addr: &Addr,
+        guest_addr: &Addr,
+    ) -> Result<NextPlayerResponse, ContractError> {
+        let games = GAMES.load(deps.storage, (host_addr, guest_addr))?;
+
+        let no_game = no_game_in_progress(host_addr, guest_addr, &games);
+        let game = games.current.ok_or(no_game)?;
+
+        let address = if game.turn == games.host {
+            host_addr
+        } else {
+            guest_addr
+        };
+        Ok(NextPlayerResponse {
+            symbol: game.turn,
+            address: address.to_string(),
+        })
+    }
+
+    pub fn can_play(
+        deps: Deps,
+        host_addr: &Addr,
+        guest_addr: &Addr,
+        player_addr: &Addr,
+        cell: usize,
+    ) -> Result<CanPlayResponse, ContractError> {
+        let games = GAMES.load(deps.storage, (host_addr, guest_addr))?;
+
+        let no_game = no_game_in_progress(host_addr, guest_addr, &games);
+        let mut game = games.current.ok_or(no_game)?;
+
+        let player =
+            player_role(games.host, host_addr, guest_addr, player_addr).unwrap_or(Player::None);
+        // Play on the loaded copy of the game, nothing is saved.
+        let result = Game::check_cell(cell).and_then(|cell| game.play(player, cell));
+        Ok(CanPlayResponse {
+            allowed: result.is_ok(),
+            reason: result.err().map(|err| err.to_string()),
+        })
+    }
+
+    pub fn move_preview(
+        deps: Deps,
+        host_addr: &Addr,
+        guest_addr: &Addr,
+        cell: usize,
+    ) -> Result<MovePreviewResponse, ContractError> {
+        let games = GAMES.load(deps.storage, (host_addr, guest_addr))?;
+
+        let no_game = no_game_in_progress(host_addr, guest_addr, &games);
+        let mut game = games.current.ok_or(no_game)?;
+
+        // Play on the loaded copy of the game for the player whose turn it is, nothing is saved.
+        let legal = Game::check_cell(cell)
+            .and_then(|cell| game.play(game.turn, cell))
+            .is_ok();
+        let result_if_played = legal.then(|| game.result());
+        Ok(MovePreviewResponse {
+            legal,
+            ends_game: legal && game.is_over(),
+            result_if_played,
+        })
+    }
+
+    pub fn validate_game(
+        deps: Deps,
+        host_addr: &Addr,
+        guest_addr: &Addr,
+    ) -> Result<ValidateGameResponse, ContractError> {
+        let games = GAMES.load(deps.storage, (host_addr, guest_addr))?;
+
+        let no_game = no_game_in_progress(host_addr, guest_addr, &games);
+        let game = games.current.ok_or(no_game)?;
+
+        let expected_turn = game.derive_turn();
+        Ok(ValidateGameResponse {
+            turn: game.turn,
+            expected_turn,
+            valid: game.turn == expected_turn,
+        })
+    }
+
+    pub fn match_status(
+        deps: Deps,
+        host_addr: &Addr,
+        guest_addr: &Addr,
+    ) -> Result<MatchStatusResponse, ContractError> {
+        let games = match GAMES.may_load(deps.storage, (host_addr, guest_addr))? {
+            Some(games) => games,
+            None => {
+                return Ok(MatchStatusResponse {
+                    host_score: 0,
+                    guest_score: 0,
+                    target: None,
+                    match_over: false,
+                    match_winner: None,
                 })
+            }
+        };
+
+        let match_winner = games.match_winner().map(|winner| {
+            if winner == games.host {
+                host_addr.to_string()
+            } else {
+                guest_addr.to_string()
+            }
+        });
+
+        Ok(MatchStatusResponse {
+            host_score: games.host_score,
+            guest_score: games.guest_score,
+            target: games.match_target,
+            match_over: match_winner.is_some(),
+            match_winner,
+        })
+    }
+
+    pub fn nickname(deps: Deps, player_addr: &Addr) -> Result<NicknameResponse, ContractError> {
+        let nickname = NICKNAMES.may_load(deps.storage, player_addr)?;
+        Ok(NicknameResponse { nickname })
+    }
+
+    pub fn chat(
+        deps: Deps,
+        host_addr: &Addr,
+        guest_addr: &Addr,
+    ) -> Result<Vec<(Addr, String)>, ContractError> {
+        let games = GAMES.load(deps.storage, (host_addr, guest_addr))?;
+        Ok(games.chat)
+    }
+
+    /// Builds the information about the games between two players.
+    fn games_info(
+        storage: &dyn Storage,
+        host_addr: &Addr,
+        guest_addr: &Addr,
+        games: Games,
+    ) -> StdResult<GamesInfo> {
+        let completed_results = games
+            .completed
+            .iter()
+            .map(|game| CompletedGameInfo {
+                game: *game,
+                result: game.result(),
+                move_count: game.move_count(),
+                winner_addr: winner_addr(game, games.host, host_addr, guest_addr),
             })
             .collect();
 
-        Ok(AllGamesListResponse { games: games? })
+        Ok(GamesInfo {
+            host: host_addr.to_string(),
+            guest: guest_addr.to_string(),
+            host_nickname: NICKNAMES.may_load(storage, host_addr)?,
+            guest_nickname: NICKNAMES.may_load(storage, guest_addr)?,
+            game_ref: game_ref(host_addr, guest_addr, games.completed_count()),
+            host_role: games.host,
+            guest_role: games.host.opponent(),
+            status: games.status(),
+            pending_invitation: games.pending_invition,
+            invite_message: games.invite_message,
+            current_game: games.current,
+            completed_games: games.completed,
+            completed_results,
+        })
     }
 }
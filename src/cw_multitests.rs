@@ -1,11 +1,20 @@
 use crate::{
-    contract,
+    contract::{self, game_ref},
     error::ContractError,
-    game::{Game, GameError, Player},
-    msg::{AllGamesListResponse, ExecuteMsg, GamesInfo, GamesResponse, InstantiateMsg, QueryMsg},
+    game::{Game, GameError, GameResult, Player},
+    msg::{
+        AllGamesListResponse, AllPairsResponse, CanPlayResponse, CompletedGameInfo,
+        CompletedGameResponse, DashboardResponse, EvaluatePositionResponse, ExecuteMsg,
+        GamesCountResponse, GamesInfo, GamesResponse, GlobalStatsResponse, InstantiateMsg,
+        LastGameResponse, MatchStatusResponse, MovePreviewResponse, NextPlayerResponse,
+        NicknameResponse, OddsResponse, PendingInvitesResponse, QueryMsg, ResultFilter,
+        SuggestMoveResponse, TotalGamesResponse, ValidateGameResponse,
+    },
+    state::{Config, GameStatus, PlayerStats, DEFAULT_INVITE_TIMEOUT, DEFAULT_MOVE_TIMEOUT, GAMES},
 };
 use anyhow::Error;
-use cosmwasm_std::{Addr, StdError};
+use cosmwasm_std::{coins, Addr, Coin, StdError, Timestamp};
+use cw2::ContractVersion;
 use cw_multi_test::{App, AppResponse, ContractWrapper, Executor};
 
 /// This is a helper struct to make testing easier.
@@ -16,13 +25,26 @@ pub struct GameMock {
 impl GameMock {
     /// Creates a new GameMock instance.
     pub fn new() -> Self {
+        Self::instantiate_with(&InstantiateMsg {
+            admin: None,
+            invite_timeout: None,
+            move_timeout: None,
+            store_history: None,
+            max_active_per_player: None,
+            max_illegal_moves: None,
+            invite_cooldown: None,
+        })
+    }
+
+    /// Creates a new GameMock instance, instantiating the contract with the given message.
+    pub fn instantiate_with(msg: &InstantiateMsg) -> Self {
         let mut app = App::default();
         let code = ContractWrapper::new(contract::execute, contract::instantiate, contract::query);
         let code_id = app.store_code(Box::new(code));
         let sender = Addr::unchecked("Owner");
 
         let contract_addr = app
-            .instantiate_contract(code_id, sender, &InstantiateMsg {}, &[], "Contract", None)
+            .instantiate_contract(code_id, sender, msg, &[], "Contract", None)
             .unwrap();
         Self { app, contract_addr }
     }
@@ -33,13 +55,80 @@ impl GameMock {
         self.accept(host, guest).unwrap();
     }
 
+    /// Mints the given funds to an address.
+    pub fn fund(&mut self, addr: &str, funds: Vec<Coin>) {
+        self.app
+            .init_modules(|router, _, storage| {
+                router
+                    .bank
+                    .init_balance(storage, &Addr::unchecked(addr), funds)
+            })
+            .unwrap();
+    }
+
     /// Simulates a player sending an invitation to another player.
     pub fn invite(&mut self, host: &str, guest: &str) -> Result<AppResponse, Error> {
+        self.invite_with_funds(host, guest, &[])
+    }
+
+    /// Simulates a player sending an invitation with funds attached.
+    pub fn invite_with_funds(
+        &mut self,
+        host: &str,
+        guest: &str,
+        funds: &[Coin],
+    ) -> Result<AppResponse, Error> {
+        self.app.execute_contract(
+            Addr::unchecked(host),
+            self.contract_addr.clone(),
+            &ExecuteMsg::Invite {
+                guest: guest.to_string(),
+                match_target: None,
+                symbol: None,
+                message: None,
+                time_bank: None,
+            },
+            funds,
+        )
+    }
+
+    /// Simulates a player sending an invitation with a message.
+    pub fn invite_with_message(
+        &mut self,
+        host: &str,
+        guest: &str,
+        message: &str,
+    ) -> Result<AppResponse, Error> {
+        self.app.execute_contract(
+            Addr::unchecked(host),
+            self.contract_addr.clone(),
+            &ExecuteMsg::Invite {
+                guest: guest.to_string(),
+                match_target: None,
+                symbol: None,
+                message: Some(message.to_string()),
+                time_bank: None,
+            },
+            &[],
+        )
+    }
+
+    /// Simulates a player inviting another player to a match won by the first to win `target` games.
+    pub fn invite_match(
+        &mut self,
+        host: &str,
+        guest: &str,
+        target: u32,
+    ) -> Result<AppResponse, Error> {
         self.app.execute_contract(
             Addr::unchecked(host),
             self.contract_addr.clone(),
             &ExecuteMsg::Invite {
                 guest: guest.to_string(),
+                match_target: Some(target),
+                symbol: None,
+                message: None,
+                time_bank: None,
             },
             &[],
         )
@@ -47,11 +136,119 @@ impl GameMock {
 
     /// Sends an acceptance of an invitation.
     pub fn accept(&mut self, host: &str, guest: &str) -> Result<AppResponse, Error> {
+        self.accept_with_preset(host, guest, None)
+    }
+
+    /// Sends an acceptance of an invitation, starting the game from the given preset.
+    pub fn accept_with_preset(
+        &mut self,
+        host: &str,
+        guest: &str,
+        preset: Option<Vec<(usize, Player)>>,
+    ) -> Result<AppResponse, Error> {
+        self.app.execute_contract(
+            Addr::unchecked(guest),
+            self.contract_addr.clone(),
+            &ExecuteMsg::Accept {
+                host: host.to_string(),
+                preset,
+                requested_symbol: None,
+            },
+            &[],
+        )
+    }
+
+    /// Simulates a player inviting several players at once.
+    pub fn invite_many(&mut self, host: &str, guests: &[&str]) -> Result<AppResponse, Error> {
+        self.app.execute_contract(
+            Addr::unchecked(host),
+            self.contract_addr.clone(),
+            &ExecuteMsg::InviteMany {
+                guests: guests.iter().map(|guest| guest.to_string()).collect(),
+            },
+            &[],
+        )
+    }
+
+    /// Simulates a player inviting another player to games played with a time bank of `seconds` each.
+    pub fn invite_with_time_bank(
+        &mut self,
+        host: &str,
+        guest: &str,
+        seconds: u64,
+    ) -> Result<AppResponse, Error> {
+        self.app.execute_contract(
+            Addr::unchecked(host),
+            self.contract_addr.clone(),
+            &ExecuteMsg::Invite {
+                guest: guest.to_string(),
+                match_target: None,
+                symbol: None,
+                message: None,
+                time_bank: Some(seconds),
+            },
+            &[],
+        )
+    }
+
+    /// Simulates a host inviting a guest while choosing the symbol they play.
+    pub fn invite_as(
+        &mut self,
+        host: &str,
+        guest: &str,
+        symbol: Option<Player>,
+    ) -> Result<AppResponse, Error> {
+        self.app.execute_contract(
+            Addr::unchecked(host),
+            self.contract_addr.clone(),
+            &ExecuteMsg::Invite {
+                guest: guest.to_string(),
+                match_target: None,
+                symbol,
+                message: None,
+                time_bank: None,
+            },
+            &[],
+        )
+    }
+
+    /// Sends an acceptance of an invitation, requesting the symbol the guest plays.
+    pub fn accept_as(
+        &mut self,
+        host: &str,
+        guest: &str,
+        requested_symbol: Option<Player>,
+    ) -> Result<AppResponse, Error> {
         self.app.execute_contract(
             Addr::unchecked(guest),
             self.contract_addr.clone(),
             &ExecuteMsg::Accept {
                 host: host.to_string(),
+                preset: None,
+                requested_symbol,
+            },
+            &[],
+        )
+    }
+
+    /// Advances the block time by the given number of seconds.
+    pub fn advance_time(&mut self, seconds: u64) {
+        self.app
+            .update_block(|block| block.time = block.time.plus_seconds(seconds));
+    }
+
+    /// Returns the current block time.
+    pub fn block_time(&self) -> Timestamp {
+        self.app.block_info().time
+    }
+
+    /// Expires a pending invitation sent by the host.
+    pub fn expire_invite(&mut self, host: &str, guest: &str) -> Result<AppResponse, Error> {
+        self.app.execute_contract(
+            Addr::unchecked(host),
+            self.contract_addr.clone(),
+            &ExecuteMsg::ExpireInvite {
+                guest: guest.to_string(),
             },
             &[],
         )
@@ -59,11 +256,22 @@ impl GameMock {
 
     /// Sends a rejection of an invitation.
     pub fn reject(&mut self, host: &str, guest: &str) -> Result<AppResponse, Error> {
+        self.reject_with_reason(host, guest, None)
+    }
+
+    /// Sends a rejection of an invitation with an optional reason.
+    pub fn reject_with_reason(
+        &mut self,
+        host: &str,
+        guest: &str,
+        reason: Option<&str>,
+    ) -> Result<AppResponse, Error> {
         self.app.execute_contract(
             Addr::unchecked(guest),
             self.contract_addr.clone(),
             &ExecuteMsg::Reject {
                 host: host.to_string(),
+                reason: reason.map(str::to_string),
             },
             &[],
         )
@@ -71,359 +279,4093 @@ impl GameMock {
 
     /// Executes a play by the given player.
     pub fn play(&mut self, player: &str, cell: usize) -> Result<AppResponse, Error> {
+        self.play_in(player, "host", "guest", cell)
+    }
+
+    /// Executes a play by the given player in the game of the given host and guest.
+    pub fn play_in(
+        &mut self,
+        player: &str,
+        host: &str,
+        guest: &str,
+        cell: usize,
+    ) -> Result<AppResponse, Error> {
+        self.app.execute_contract(
+            Addr::unchecked(player),
+            self.contract_addr.clone(),
+            &ExecuteMsg::Play {
+                host: host.to_string(),
+                guest: guest.to_string(),
+                cell,
+                nonce: None,
+            },
+            &[],
+        )
+    }
+
+    /// Executes a play by the given player along with a nonce.
+    pub fn play_with_nonce(
+        &mut self,
+        player: &str,
+        host: &str,
+        guest: &str,
+        cell: usize,
+        nonce: u64,
+    ) -> Result<AppResponse, Error> {
         self.app.execute_contract(
             Addr::unchecked(player),
             self.contract_addr.clone(),
             &ExecuteMsg::Play {
+                host: host.to_string(),
+                guest: guest.to_string(),
+                cell,
+                nonce: Some(nonce),
+            },
+            &[],
+        )
+    }
+
+    /// Transfers the seat of the given player to a new address.
+    pub fn transfer_seat(
+        &mut self,
+        player: &str,
+        host: &str,
+        guest: &str,
+        new_addr: &str,
+    ) -> Result<AppResponse, Error> {
+        self.app.execute_contract(
+            Addr::unchecked(player),
+            self.contract_addr.clone(),
+            &ExecuteMsg::TransferSeat {
+                host: host.to_string(),
+                guest: guest.to_string(),
+                new_addr: new_addr.to_string(),
+            },
+            &[],
+        )
+    }
+
+    /// Executes a play at the given row and column by the given player.
+    pub fn play_at(&mut self, player: &str, row: usize, col: usize) -> Result<AppResponse, Error> {
+        self.app.execute_contract(
+            Addr::unchecked(player),
+            self.contract_addr.clone(),
+            &ExecuteMsg::PlayAt {
                 host: "host".to_string(),
                 guest: "guest".to_string(),
-                cell,
+                row,
+                col,
             },
             &[],
         )
     }
 
-    /// Queries the contract for the games of the given host and guest.
-    pub fn query_games(&self, host: &str, guest: &str) -> Result<GamesResponse, StdError> {
-        self.app.wrap().query_wasm_smart(
+    /// Executes a sequence of plays by the given player.
+    pub fn play_sequence(
+        &mut self,
+        player: &str,
+        host: &str,
+        guest: &str,
+        cells: Vec<usize>,
+    ) -> Result<AppResponse, Error> {
+        self.app.execute_contract(
+            Addr::unchecked(player),
             self.contract_addr.clone(),
-            &QueryMsg::Games {
+            &ExecuteMsg::PlaySequence {
                 host: host.to_string(),
                 guest: guest.to_string(),
+                cells,
             },
+            &[],
         )
     }
 
-    pub fn query_all_games(&self) -> Result<AllGamesListResponse, StdError> {
-        self.app
-            .wrap()
-            .query_wasm_smart(self.contract_addr.clone(), &QueryMsg::AllGamesList {})
+    /// Resigns the given player from the game of the given host and guest.
+    pub fn resign(&mut self, player: &str, host: &str, guest: &str) -> Result<AppResponse, Error> {
+        self.app.execute_contract(
+            Addr::unchecked(player),
+            self.contract_addr.clone(),
+            &ExecuteMsg::Resign {
+                host: host.to_string(),
+                guest: guest.to_string(),
+            },
+            &[],
+        )
     }
-}
-// A macro rule to get an attribute value from an event
-macro_rules! attribute {
-    ($event:expr, $key:expr) => {
-        $event
-            .attributes
-            .iter()
-            .find(|attr| attr.key == $key)
-            .unwrap()
-            .value
-    };
-}
 
-#[test]
-fn proper_instantiation() {
-    let game_mock = GameMock::new();
+    /// Concedes the match between the given host and guest.
+    pub fn concede_match(
+        &mut self,
+        player: &str,
+        host: &str,
+        guest: &str,
+    ) -> Result<AppResponse, Error> {
+        self.app.execute_contract(
+            Addr::unchecked(player),
+            self.contract_addr.clone(),
+            &ExecuteMsg::ConcedeMatch {
+                host: host.to_string(),
+                guest: guest.to_string(),
+            },
+            &[],
+        )
+    }
 
-    let resp = game_mock.query_all_games().unwrap();
-    assert_eq!(resp, AllGamesListResponse { games: vec![] });
-}
+    /// Claims the win of a game in which the opponent did not play in time.
+    pub fn claim_timeout(
+        &mut self,
+        player: &str,
+        host: &str,
+        guest: &str,
+    ) -> Result<AppResponse, Error> {
+        self.app.execute_contract(
+            Addr::unchecked(player),
+            self.contract_addr.clone(),
+            &ExecuteMsg::ClaimTimeout {
+                host: host.to_string(),
+                guest: guest.to_string(),
+            },
+            &[],
+        )
+    }
 
-#[test]
-fn send_invitation() {
-    let mut game_mock = GameMock::new();
-    let resp = game_mock.invite("host", "guest").unwrap();
+    pub fn claim_result(
+        &mut self,
+        player: &str,
+        host: &str,
+        guest: &str,
+    ) -> Result<AppResponse, Error> {
+        self.app.execute_contract(
+            Addr::unchecked(player),
+            self.contract_addr.clone(),
+            &ExecuteMsg::ClaimResult {
+                host: host.to_string(),
+                guest: guest.to_string(),
+            },
+            &[],
+        )
+    }
 
-    let event = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
-    assert_eq!(attribute!(event, "action"), "invite");
-    assert_eq!(attribute!(event, "host"), "host");
-    assert_eq!(attribute!(event, "guest"), "guest");
+    /// Posts a chat message to the other player of a game.
+    pub fn post_message(
+        &mut self,
+        sender: &str,
+        host: &str,
+        guest: &str,
+        text: &str,
+    ) -> Result<AppResponse, Error> {
+        self.app.execute_contract(
+            Addr::unchecked(sender),
+            self.contract_addr.clone(),
+            &ExecuteMsg::PostMessage {
+                host: host.to_string(),
+                guest: guest.to_string(),
+                text: text.to_string(),
+            },
+            &[],
+        )
+    }
 
-    let resp = game_mock.query_games("host", "guest").unwrap();
-    assert_eq!(resp.info.host, "host");
-    assert_eq!(resp.info.guest, "guest");
-    assert_eq!(resp.info.pending_invitation, true);
+    /// Requests or confirms the deletion of the completed games between the given host and guest.
+    pub fn clear_my_history(
+        &mut self,
+        sender: &str,
+        host: &str,
+        guest: &str,
+    ) -> Result<AppResponse, Error> {
+        self.app.execute_contract(
+            Addr::unchecked(sender),
+            self.contract_addr.clone(),
+            &ExecuteMsg::ClearMyHistory {
+                host: host.to_string(),
+                guest: guest.to_string(),
+            },
+            &[],
+        )
+    }
+
+    /// Imports the games between the given host and guest, overwriting an existing pair if `force` is set.
+    pub fn import_game(
+        &mut self,
+        sender: &str,
+        host: &str,
+        guest: &str,
+        current: Option<Game>,
+        completed: Vec<Game>,
+        force: bool,
+    ) -> Result<AppResponse, Error> {
+        self.app.execute_contract(
+            Addr::unchecked(sender),
+            self.contract_addr.clone(),
+            &ExecuteMsg::ImportGame {
+                host: host.to_string(),
+                guest: guest.to_string(),
+                host_role: Player::X,
+                current,
+                completed,
+                force,
+            },
+            &[],
+        )
+    }
+
+    /// Deletes all the games between the given host and guest.
+    pub fn purge_pair(
+        &mut self,
+        sender: &str,
+        host: &str,
+        guest: &str,
+        force: bool,
+    ) -> Result<AppResponse, Error> {
+        self.app.execute_contract(
+            Addr::unchecked(sender),
+            self.contract_addr.clone(),
+            &ExecuteMsg::PurgePair {
+                host: host.to_string(),
+                guest: guest.to_string(),
+                force,
+            },
+            &[],
+        )
+    }
+
+    /// Sets the nickname of a player.
+    pub fn set_nickname(&mut self, player: &str, nickname: &str) -> Result<AppResponse, Error> {
+        self.app.execute_contract(
+            Addr::unchecked(player),
+            self.contract_addr.clone(),
+            &ExecuteMsg::SetNickname {
+                nickname: nickname.to_string(),
+            },
+            &[],
+        )
+    }
+
+    /// Force ends the game of the given host and guest.
+    pub fn force_end_game(
+        &mut self,
+        sender: &str,
+        host: &str,
+        guest: &str,
+    ) -> Result<AppResponse, Error> {
+        self.app.execute_contract(
+            Addr::unchecked(sender),
+            self.contract_addr.clone(),
+            &ExecuteMsg::ForceEndGame {
+                host: host.to_string(),
+                guest: guest.to_string(),
+            },
+            &[],
+        )
+    }
+
+    /// Updates the configuration of the contract.
+    pub fn update_config(
+        &mut self,
+        sender: &str,
+        invite_timeout: Option<u64>,
+        move_timeout: Option<u64>,
+    ) -> Result<AppResponse, Error> {
+        self.app.execute_contract(
+            Addr::unchecked(sender),
+            self.contract_addr.clone(),
+            &ExecuteMsg::UpdateConfig {
+                invite_timeout,
+                move_timeout,
+            },
+            &[],
+        )
+    }
+
+    /// Queries the statistics of the games completed across all players.
+    pub fn query_global_stats(&self) -> Result<GlobalStatsResponse, StdError> {
+        self.app
+            .wrap()
+            .query_wasm_smart(self.contract_addr.clone(), &QueryMsg::GlobalStats {})
+    }
+
+    /// Queries the number of pairs of players.
+    pub fn query_games_count(&self) -> Result<GamesCountResponse, StdError> {
+        self.app
+            .wrap()
+            .query_wasm_smart(self.contract_addr.clone(), &QueryMsg::GamesCount {})
+    }
+
+    /// Queries the name and version of the contract.
+    pub fn query_contract_info(&self) -> Result<ContractVersion, StdError> {
+        self.app
+            .wrap()
+            .query_wasm_smart(self.contract_addr.clone(), &QueryMsg::ContractInfo {})
+    }
+
+    /// Queries the contract for the number of completed games.
+    pub fn query_total_games(&self) -> Result<TotalGamesResponse, StdError> {
+        self.app
+            .wrap()
+            .query_wasm_smart(self.contract_addr.clone(), &QueryMsg::TotalGames {})
+    }
+
+    /// Queries the contract for its configuration.
+    pub fn query_config(&self) -> Result<Config, StdError> {
+        self.app
+            .wrap()
+            .query_wasm_smart(self.contract_addr.clone(), &QueryMsg::Config {})
+    }
+
+    /// Queries the contract for the games of the given host and guest.
+    pub fn query_games(&self, host: &str, guest: &str) -> Result<GamesResponse, StdError> {
+        self.app.wrap().query_wasm_smart(
+            self.contract_addr.clone(),
+            &QueryMsg::Games {
+                host: host.to_string(),
+                guest: guest.to_string(),
+            },
+        )
+    }
+
+    pub fn query_all_games(&self) -> Result<AllGamesListResponse, StdError> {
+        self.query_all_games_ordered(None)
+    }
+
+    /// Queries the contract for all games, optionally listed in descending order.
+    pub fn query_all_games_ordered(
+        &self,
+        descending: Option<bool>,
+    ) -> Result<AllGamesListResponse, StdError> {
+        self.app.wrap().query_wasm_smart(
+            self.contract_addr.clone(),
+            &QueryMsg::AllGamesList {
+                descending,
+                from: None,
+                to: None,
+                min_completed: None,
+                start_after: None,
+                limit: None,
+            },
+        )
+    }
+
+    /// Queries the contract for the games with a game created or completed in the given window.
+    pub fn query_all_games_between(
+        &self,
+        from: Option<Timestamp>,
+        to: Option<Timestamp>,
+    ) -> Result<AllGamesListResponse, StdError> {
+        self.app.wrap().query_wasm_smart(
+            self.contract_addr.clone(),
+            &QueryMsg::AllGamesList {
+                descending: None,
+                from,
+                to,
+                min_completed: None,
+                start_after: None,
+                limit: None,
+            },
+        )
+    }
+
+    /// Queries the contract for the games of the pairs with at least `min_completed` completed games.
+    pub fn query_all_games_with_completed(
+        &self,
+        min_completed: u32,
+    ) -> Result<AllGamesListResponse, StdError> {
+        self.app.wrap().query_wasm_smart(
+            self.contract_addr.clone(),
+            &QueryMsg::AllGamesList {
+                descending: None,
+                from: None,
+                to: None,
+                min_completed: Some(min_completed),
+                start_after: None,
+                limit: None,
+            },
+        )
+    }
+
+    /// Queries a page of all the games.
+    pub fn query_all_games_page(
+        &self,
+        start_after: Option<(String, String)>,
+        limit: u32,
+    ) -> Result<AllGamesListResponse, StdError> {
+        self.app.wrap().query_wasm_smart(
+            self.contract_addr.clone(),
+            &QueryMsg::AllGamesList {
+                descending: None,
+                from: None,
+                to: None,
+                min_completed: None,
+                start_after,
+                limit: Some(limit),
+            },
+        )
+    }
+
+    /// Queries the contract for the last completed game of the given host and guest.
+    pub fn query_last_game(&self, host: &str, guest: &str) -> Result<LastGameResponse, StdError> {
+        self.app.wrap().query_wasm_smart(
+            self.contract_addr.clone(),
+            &QueryMsg::LastGame {
+                host: host.to_string(),
+                guest: guest.to_string(),
+            },
+        )
+    }
+
+    /// Queries the contract for the best move in the current game of the given host and guest.
+    pub fn query_suggest_move(
+        &self,
+        host: &str,
+        guest: &str,
+    ) -> Result<SuggestMoveResponse, StdError> {
+        self.app.wrap().query_wasm_smart(
+            self.contract_addr.clone(),
+            &QueryMsg::SuggestMove {
+                host: host.to_string(),
+                guest: guest.to_string(),
+            },
+        )
+    }
+
+    /// Queries the completed game at the given index between the given host and guest.
+    pub fn query_completed_game(
+        &self,
+        host: &str,
+        guest: &str,
+        index: usize,
+    ) -> Result<CompletedGameResponse, StdError> {
+        self.app.wrap().query_wasm_smart(
+            self.contract_addr.clone(),
+            &QueryMsg::CompletedGame {
+                host: host.to_string(),
+                guest: guest.to_string(),
+                index,
+            },
+        )
+    }
+
+    /// Queries the board of the current game between the given host and guest as rows.
+    pub fn query_board_rows(&self, host: &str, guest: &str) -> Result<Vec<Vec<Player>>, StdError> {
+        self.app.wrap().query_wasm_smart(
+            self.contract_addr.clone(),
+            &QueryMsg::BoardRows {
+                host: host.to_string(),
+                guest: guest.to_string(),
+            },
+        )
+    }
+
+    /// Queries the theoretical result of the current game.
+    pub fn query_evaluate_position(
+        &self,
+        host: &str,
+        guest: &str,
+    ) -> Result<EvaluatePositionResponse, StdError> {
+        self.app.wrap().query_wasm_smart(
+            self.contract_addr.clone(),
+            &QueryMsg::EvaluatePosition {
+                host: host.to_string(),
+                guest: guest.to_string(),
+            },
+        )
+    }
+
+    /// Queries the contract for whether it is the given player's turn.
+    pub fn query_is_my_turn(
+        &self,
+        host: &str,
+        guest: &str,
+        player: &str,
+    ) -> Result<bool, StdError> {
+        self.app.wrap().query_wasm_smart(
+            self.contract_addr.clone(),
+            &QueryMsg::IsMyTurn {
+                host: host.to_string(),
+                guest: guest.to_string(),
+                player: player.to_string(),
+            },
+        )
+    }
+
+    /// Queries the contract for the dashboard of the given player.
+    pub fn query_dashboard(&self, player: &str) -> Result<DashboardResponse, StdError> {
+        self.app.wrap().query_wasm_smart(
+            self.contract_addr.clone(),
+            &QueryMsg::Dashboard {
+                player: player.to_string(),
+            },
+        )
+    }
+
+    /// Queries the contract for the games in which the given player is the guest.
+    pub fn query_games_as_guest(&self, guest: &str) -> Result<AllGamesListResponse, StdError> {
+        self.app.wrap().query_wasm_smart(
+            self.contract_addr.clone(),
+            &QueryMsg::GamesAsGuest {
+                guest: guest.to_string(),
+            },
+        )
+    }
+
+    /// Queries the contract for the hosts with a pending invitation for the given guest.
+    pub fn query_pending_invites_for(
+        &self,
+        guest: &str,
+    ) -> Result<PendingInvitesResponse, StdError> {
+        self.app.wrap().query_wasm_smart(
+            self.contract_addr.clone(),
+            &QueryMsg::PendingInvitesFor {
+                guest: guest.to_string(),
+            },
+        )
+    }
+
+    /// Queries whether the given host ever invited the given guest.
+    pub fn query_pair_exists(&self, host: &str, guest: &str) -> Result<bool, StdError> {
+        self.app.wrap().query_wasm_smart(
+            self.contract_addr.clone(),
+            &QueryMsg::PairExists {
+                host: host.to_string(),
+                guest: guest.to_string(),
+            },
+        )
+    }
+
+    /// Queries a page of the pairs of players, in storage order.
+    pub fn query_all_pairs(
+        &self,
+        start_after: Option<(&str, &str)>,
+        limit: Option<u32>,
+    ) -> Result<AllPairsResponse, StdError> {
+        self.app.wrap().query_wasm_smart(
+            self.contract_addr.clone(),
+            &QueryMsg::AllPairs {
+                start_after: start_after.map(|(host, guest)| (host.to_string(), guest.to_string())),
+                limit,
+            },
+        )
+    }
+
+    /// Queries the games between several pairs of players.
+    pub fn query_games_batch(
+        &self,
+        pairs: &[(&str, &str)],
+    ) -> Result<Vec<Option<GamesInfo>>, StdError> {
+        self.app.wrap().query_wasm_smart(
+            self.contract_addr.clone(),
+            &QueryMsg::GamesBatch {
+                pairs: pairs
+                    .iter()
+                    .map(|(host, guest)| (host.to_string(), guest.to_string()))
+                    .collect(),
+            },
+        )
+    }
+
+    /// Queries the raw storage key of the games between the given host and guest.
+    pub fn query_storage_key(&self, host: &str, guest: &str) -> Result<String, StdError> {
+        self.app.wrap().query_wasm_smart(
+            self.contract_addr.clone(),
+            &QueryMsg::StorageKey {
+                host: host.to_string(),
+                guest: guest.to_string(),
+            },
+        )
+    }
+
+    /// Queries the players the given player has a game in progress with.
+    pub fn query_active_opponents(&self, player: &str) -> Result<Vec<String>, StdError> {
+        self.app.wrap().query_wasm_smart(
+            self.contract_addr.clone(),
+            &QueryMsg::ActiveOpponents {
+                player: player.to_string(),
+            },
+        )
+    }
+
+    /// Queries the contract for all the games with the given status.
+    pub fn query_games_by_status(
+        &self,
+        status: GameStatus,
+    ) -> Result<AllGamesListResponse, StdError> {
+        self.app.wrap().query_wasm_smart(
+            self.contract_addr.clone(),
+            &QueryMsg::GamesByStatus { status },
+        )
+    }
+
+    /// Queries the contract for the completed games with the given result.
+    pub fn query_games_by_result(
+        &self,
+        result: ResultFilter,
+    ) -> Result<AllGamesListResponse, StdError> {
+        self.app.wrap().query_wasm_smart(
+            self.contract_addr.clone(),
+            &QueryMsg::GamesByResult { result },
+        )
+    }
+
+    /// Queries the statistics of a player.
+    pub fn query_stats(&self, player: &str) -> Result<PlayerStats, StdError> {
+        self.app.wrap().query_wasm_smart(
+            self.contract_addr.clone(),
+            &QueryMsg::Stats {
+                player: player.to_string(),
+            },
+        )
+    }
+
+    /// Queries the nickname of a player.
+    pub fn query_nickname(&self, player: &str) -> Result<NicknameResponse, StdError> {
+        self.app.wrap().query_wasm_smart(
+            self.contract_addr.clone(),
+            &QueryMsg::Nickname {
+                player: player.to_string(),
+            },
+        )
+    }
+
+    /// Queries the chances of each outcome of the current game between the given host and guest.
+    pub fn query_odds(&self, host: &str, guest: &str) -> Result<OddsResponse, StdError> {
+        self.app.wrap().query_wasm_smart(
+            self.contract_addr.clone(),
+            &QueryMsg::Odds {
+                host: host.to_string(),
+                guest: guest.to_string(),
+            },
+        )
+    }
+
+    /// Queries the winning combinations the player could still complete in the current game.
+    pub fn query_threats(
+        &self,
+        host: &str,
+        guest: &str,
+        player: Player,
+    ) -> Result<Vec<[usize; 3]>, StdError> {
+        self.app.wrap().query_wasm_smart(
+            self.contract_addr.clone(),
+            &QueryMsg::Threats {
+                host: host.to_string(),
+                guest: guest.to_string(),
+                player,
+            },
+        )
+    }
+
+    /// Queries the number of empty cells of the current game.
+    pub fn query_empty_count(&self, host: &str, guest: &str) -> Result<usize, StdError> {
+        self.app.wrap().query_wasm_smart(
+            self.contract_addr.clone(),
+            &QueryMsg::EmptyCount {
+                host: host.to_string(),
+                guest: guest.to_string(),
+            },
+        )
+    }
+
+    /// Queries the packed board of the current game.
+    pub fn query_packed_board(&self, host: &str, guest: &str) -> Result<u32, StdError> {
+        self.app.wrap().query_wasm_smart(
+            self.contract_addr.clone(),
+            &QueryMsg::PackedBoard {
+                host: host.to_string(),
+                guest: guest.to_string(),
+            },
+        )
+    }
+
+    /// Queries a winning or blocking move for the player whose turn it is in the current game.
+    pub fn query_hint(&self, host: &str, guest: &str) -> Result<Option<usize>, StdError> {
+        self.app.wrap().query_wasm_smart(
+            self.contract_addr.clone(),
+            &QueryMsg::Hint {
+                host: host.to_string(),
+                guest: guest.to_string(),
+            },
+        )
+    }
+
+    /// Queries the chat messages posted by the given host and guest.
+    pub fn query_chat(&self, host: &str, guest: &str) -> Result<Vec<(Addr, String)>, StdError> {
+        self.app.wrap().query_wasm_smart(
+            self.contract_addr.clone(),
+            &QueryMsg::Chat {
+                host: host.to_string(),
+                guest: guest.to_string(),
+            },
+        )
+    }
+
+    /// Queries the symbol and the address of the player whose turn it is in the current game.
+    pub fn query_next_player(
+        &self,
+        host: &str,
+        guest: &str,
+    ) -> Result<NextPlayerResponse, StdError> {
+        self.app.wrap().query_wasm_smart(
+            self.contract_addr.clone(),
+            &QueryMsg::NextPlayer {
+                host: host.to_string(),
+                guest: guest.to_string(),
+            },
+        )
+    }
+
+    /// Queries the address of the winner of the current or last game between the given host and guest.
+    pub fn query_winner(&self, host: &str, guest: &str) -> Result<Option<String>, StdError> {
+        self.app.wrap().query_wasm_smart(
+            self.contract_addr.clone(),
+            &QueryMsg::Winner {
+                host: host.to_string(),
+                guest: guest.to_string(),
+            },
+        )
+    }
+
+    /// Queries the symbol played by the player in the games between the given host and guest.
+    pub fn query_my_symbol(
+        &self,
+        host: &str,
+        guest: &str,
+        player: &str,
+    ) -> Result<Player, StdError> {
+        self.app.wrap().query_wasm_smart(
+            self.contract_addr.clone(),
+            &QueryMsg::MySymbol {
+                host: host.to_string(),
+                guest: guest.to_string(),
+                player: player.to_string(),
+            },
+        )
+    }
+
+    /// Queries whether the player can play in the given cell of the current game.
+    pub fn query_can_play(
+        &self,
+        host: &str,
+        guest: &str,
+        player: &str,
+        cell: usize,
+    ) -> Result<CanPlayResponse, StdError> {
+        self.app.wrap().query_wasm_smart(
+            self.contract_addr.clone(),
+            &QueryMsg::CanPlay {
+                host: host.to_string(),
+                guest: guest.to_string(),
+                player: player.to_string(),
+                cell,
+            },
+        )
+    }
+
+    /// Queries the outcome of playing the given cell in the current game.
+    pub fn query_move_preview(
+        &self,
+        host: &str,
+        guest: &str,
+        cell: usize,
+    ) -> Result<MovePreviewResponse, StdError> {
+        self.app.wrap().query_wasm_smart(
+            self.contract_addr.clone(),
+            &QueryMsg::MovePreview {
+                host: host.to_string(),
+                guest: guest.to_string(),
+                cell,
+            },
+        )
+    }
+
+    /// Queries the consistency of the current game between the given host and guest.
+    pub fn query_validate_game(
+        &self,
+        host: &str,
+        guest: &str,
+    ) -> Result<ValidateGameResponse, StdError> {
+        self.app.wrap().query_wasm_smart(
+            self.contract_addr.clone(),
+            &QueryMsg::ValidateGame {
+                host: host.to_string(),
+                guest: guest.to_string(),
+            },
+        )
+    }
+
+    /// Queries the scores of the match between the given host and guest.
+    pub fn query_match_status(
+        &self,
+        host: &str,
+        guest: &str,
+    ) -> Result<MatchStatusResponse, StdError> {
+        self.app.wrap().query_wasm_smart(
+            self.contract_addr.clone(),
+            &QueryMsg::MatchStatus {
+                host: host.to_string(),
+                guest: guest.to_string(),
+            },
+        )
+    }
+}
+// A macro rule to get an attribute value from an event
+macro_rules! attribute {
+    ($event:expr, $key:expr) => {
+        $event
+            .attributes
+            .iter()
+            .find(|attr| attr.key == $key)
+            .unwrap()
+            .value
+    };
+}
+
+#[test]
+fn proper_instantiation() {
+    let game_mock = GameMock::new();
+
+    let resp = game_mock.query_all_games().unwrap();
+    assert_eq!(
+        resp,
+        AllGamesListResponse {
+            games: vec![],
+            next_cursor: None
+        }
+    );
+}
+
+#[test]
+fn all_games_list_order() {
+    let mut game_mock = GameMock::new();
+    game_mock.invite("host1", "guest").unwrap();
+    game_mock.invite("host2", "guest").unwrap();
+    game_mock.invite("host3", "guest").unwrap();
+
+    let hosts = |resp: AllGamesListResponse| -> Vec<String> {
+        resp.games.into_iter().map(|info| info.host).collect()
+    };
+    let ascending = hosts(game_mock.query_all_games().unwrap());
+    assert_eq!(ascending, ["host1", "host2", "host3"]);
+
+    let descending = hosts(game_mock.query_all_games_ordered(Some(true)).unwrap());
+    assert_eq!(descending, ["host3", "host2", "host1"]);
+}
+
+#[test]
+fn all_games_list_window() {
+    let mut game_mock = GameMock::new();
+    let start = game_mock.block_time();
+    game_mock.init_game("host1", "guest");
+    game_mock.resign("guest", "host1", "guest").unwrap();
+    game_mock.advance_time(100);
+    game_mock.init_game("host2", "guest");
+    game_mock.advance_time(100);
+    game_mock.init_game("host3", "guest");
+    // A pair without any game is only listed without a window.
+    game_mock.invite("host4", "guest").unwrap();
+
+    let hosts = |resp: AllGamesListResponse| -> Vec<String> {
+        resp.games.into_iter().map(|info| info.host).collect()
+    };
+    let window = game_mock
+        .query_all_games_between(Some(start.plus_seconds(50)), Some(start.plus_seconds(150)))
+        .unwrap();
+    assert_eq!(hosts(window), ["host2"]);
+
+    let window = game_mock
+        .query_all_games_between(Some(start.plus_seconds(100)), None)
+        .unwrap();
+    assert_eq!(hosts(window), ["host2", "host3"]);
+
+    let window = game_mock
+        .query_all_games_between(None, Some(start))
+        .unwrap();
+    assert_eq!(hosts(window), ["host1"]);
+
+    assert_eq!(hosts(game_mock.query_all_games().unwrap()).len(), 4);
+}
+
+#[test]
+fn global_stats() {
+    let mut game_mock = GameMock::new();
+
+    // A draw.
+    game_mock.init_game("host", "guest");
+    for (player, cell) in [
+        ("host", 0),
+        ("guest", 1),
+        ("host", 2),
+        ("guest", 4),
+        ("host", 3),
+        ("guest", 5),
+        ("host", 7),
+        ("guest", 6),
+        ("host", 8),
+    ] {
+        game_mock.play(player, cell).unwrap();
+    }
+
+    // A decisive game and a resignation.
+    game_mock.init_game("host", "guest");
+    for (player, cell) in [
+        ("host", 0),
+        ("guest", 1),
+        ("host", 3),
+        ("guest", 5),
+        ("host", 6),
+    ] {
+        game_mock.play(player, cell).unwrap();
+    }
+    game_mock.init_game("host", "guest");
+    game_mock.resign("host", "host", "guest").unwrap();
+
+    assert_eq!(
+        game_mock.query_global_stats().unwrap(),
+        GlobalStatsResponse {
+            total_completed: 3,
+            total_draws: 1,
+        }
+    );
+}
+
+#[test]
+fn without_history() {
+    let mut game_mock = GameMock::instantiate_with(&InstantiateMsg {
+        admin: None,
+        invite_timeout: None,
+        move_timeout: None,
+        store_history: Some(false),
+        max_active_per_player: None,
+        max_illegal_moves: None,
+        invite_cooldown: None,
+    });
+    game_mock.init_game("host", "guest");
+    for (player, cell) in [
+        ("host", 0),
+        ("guest", 1),
+        ("host", 3),
+        ("guest", 5),
+        ("host", 6),
+    ] {
+        game_mock.play(player, cell).unwrap();
+    }
+
+    let info = game_mock.query_games("host", "guest").unwrap().info;
+    assert_eq!(info.status, GameStatus::Completed);
+    assert!(info.completed_games.is_empty());
+    // The statistics still count the game.
+    assert_eq!(game_mock.query_total_games().unwrap().total_completed, 1);
+
+    // The next game gets another reference, even though the first one was not kept.
+    let first_ref = game_ref(&Addr::unchecked("host"), &Addr::unchecked("guest"), 0);
+    let resp = game_mock.invite("host", "guest").unwrap();
+    let event = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+    assert_ne!(attribute!(event, "game_ref"), first_ref);
+}
+
+#[test]
+fn games_count() {
+    let mut game_mock = GameMock::new();
+    assert_eq!(game_mock.query_games_count().unwrap().count, 0);
+
+    game_mock.init_game("host1", "guest");
+    game_mock.invite("host2", "guest").unwrap();
+    game_mock.invite("host1", "other").unwrap();
+    // Playing again does not create a new pair.
+    game_mock.resign("guest", "host1", "guest").unwrap();
+    game_mock.invite("host1", "guest").unwrap();
+
+    assert_eq!(
+        game_mock.query_games_count().unwrap(),
+        GamesCountResponse { count: 3 }
+    );
+}
+
+#[test]
+fn config_readback() {
+    let game_mock = GameMock::new();
+
+    let config = game_mock.query_config().unwrap();
+    assert_eq!(
+        Config {
+            admin: Addr::unchecked("Owner"),
+            invite_timeout: DEFAULT_INVITE_TIMEOUT,
+            move_timeout: DEFAULT_MOVE_TIMEOUT,
+            store_history: true,
+            max_active_per_player: None,
+            max_illegal_moves: None,
+            invite_cooldown: 0,
+        },
+        config
+    );
+}
+
+#[test]
+fn funded_instantiation() {
+    let mut app = App::default();
+    let code = ContractWrapper::new(contract::execute, contract::instantiate, contract::query);
+    let code_id = app.store_code(Box::new(code));
+    let sender = Addr::unchecked("Owner");
+    app.init_modules(|router, _, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, coins(100, "utoken"))
+    })
+    .unwrap();
+
+    let err = app
+        .instantiate_contract(
+            code_id,
+            sender,
+            &InstantiateMsg {
+                admin: None,
+                invite_timeout: None,
+                move_timeout: None,
+                store_history: None,
+                max_active_per_player: None,
+                max_illegal_moves: None,
+                invite_cooldown: None,
+            },
+            &coins(100, "utoken"),
+            "Contract",
+            None,
+        )
+        .unwrap_err();
+    assert_eq!(ContractError::UnexpectedFunds, err.downcast().unwrap());
+}
+
+#[test]
+fn contract_info() {
+    let game_mock = GameMock::new();
+
+    let info = game_mock.query_contract_info().unwrap();
+    assert_eq!(
+        info,
+        ContractVersion {
+            contract: "crates.io:tic-tac-toe".to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    );
+}
+
+#[test]
+fn update_config() {
+    let mut game_mock = GameMock::new();
+
+    let resp = game_mock.update_config("Owner", None, Some(60)).unwrap();
+    let event = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+    assert_eq!(attribute!(event, "action"), "update_config");
+    assert_eq!(attribute!(event, "move_timeout"), "60");
+
+    let config = game_mock.query_config().unwrap();
+    assert_eq!(config.move_timeout, 60);
+    assert_eq!(config.invite_timeout, DEFAULT_INVITE_TIMEOUT);
+}
+
+#[test]
+fn update_config_unauthorized() {
+    let mut game_mock = GameMock::new();
+
+    let err = game_mock.update_config("host", None, Some(60)).unwrap_err();
+    assert_eq!(ContractError::Unauthorized, err.downcast().unwrap());
+
+    let config = game_mock.query_config().unwrap();
+    assert_eq!(config.move_timeout, DEFAULT_MOVE_TIMEOUT);
+}
+
+#[test]
+fn update_config_zero_timeout() {
+    let mut game_mock = GameMock::new();
+
+    let err = game_mock.update_config("Owner", None, Some(0)).unwrap_err();
+    assert_eq!(
+        ContractError::InvalidConfig {
+            reason: "move timeout must be greater than zero".to_string()
+        },
+        err.downcast().unwrap()
+    );
+}
+
+#[test]
+fn send_invitation() {
+    let mut game_mock = GameMock::new();
+    let resp = game_mock.invite("host", "guest").unwrap();
+
+    let event = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+    assert_eq!(attribute!(event, "action"), "invite");
+    assert_eq!(attribute!(event, "host"), "host");
+    assert_eq!(attribute!(event, "guest"), "guest");
+    assert_eq!(attribute!(event, "is_new_pair"), "true");
+
+    let resp = game_mock.query_games("host", "guest").unwrap();
+    assert_eq!(resp.info.host, "host");
+    assert_eq!(resp.info.guest, "guest");
+    assert!(resp.info.pending_invitation);
+}
+
+#[test]
+fn invitation_with_funds() {
+    let mut game_mock = GameMock::new();
+    game_mock.fund("host", coins(100, "utoken"));
+
+    let err = game_mock
+        .invite_with_funds("host", "guest", &coins(10, "utoken"))
+        .unwrap_err();
+    assert_eq!(ContractError::UnexpectedFunds, err.downcast().unwrap());
+
+    // The invitation was not recorded.
+    assert!(game_mock.query_games("host", "guest").is_err());
+}
+
+#[test]
+fn rematch_invitation() {
+    let mut game_mock = GameMock::new();
+    game_mock.init_game("host", "guest");
+    game_mock.play("host", 0).unwrap();
+    game_mock.play("guest", 3).unwrap();
+    game_mock.play("host", 1).unwrap();
+    game_mock.play("guest", 4).unwrap();
+    game_mock.play("host", 2).unwrap();
+
+    let resp = game_mock.invite("host", "guest").unwrap();
+    let event = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+    assert_eq!(attribute!(event, "action"), "invite");
+    assert_eq!(attribute!(event, "is_new_pair"), "false");
+}
+
+#[test]
+fn invitation_already_pending() {
+    let mut game_mock = GameMock::new();
+    game_mock.invite("host", "guest").unwrap();
+
+    let err = game_mock.invite("host", "guest").unwrap_err();
+    assert_eq!(
+        ContractError::InvitationAlreadyPending {
+            host: "host".to_string(),
+            guest: "guest".to_string()
+        },
+        err.downcast().unwrap()
+    );
+}
+
+#[test]
+fn invalid_invitation_game_in_progress() {
+    let mut game_mock = GameMock::new();
+    game_mock.init_game("host", "guest");
+
+    let err = game_mock.invite("host", "guest").unwrap_err();
+    assert_eq!(
+        ContractError::GameInProgress {
+            host: "host".to_string(),
+            guest: "guest".to_string()
+        },
+        err.downcast().unwrap()
+    );
+}
+
+#[test]
+fn accept_invitation() {
+    let mut game_mock = GameMock::new();
+    game_mock.invite("host", "guest").unwrap();
+
+    let resp = game_mock.accept("host", "guest").unwrap();
+    let event = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+    assert_eq!(attribute!(event, "action"), "accept_invite");
+    assert_eq!(attribute!(event, "host"), "host");
+    assert_eq!(attribute!(event, "guest"), "guest");
+
+    let resp = game_mock.query_games("host", "guest").unwrap();
+    assert_eq!(resp.info.host, "host");
+    assert_eq!(resp.info.guest, "guest");
+    assert!(!resp.info.pending_invitation);
+    assert_eq!(resp.info.current_game.unwrap().board(), &[Player::None; 9]);
+    assert_eq!(resp.info.current_game.unwrap().turn(), Player::X);
+}
+
+#[test]
+fn accept_invitation_with_preset() {
+    let mut game_mock = GameMock::new();
+    game_mock.invite("host", "guest").unwrap();
+
+    let preset = vec![
+        (0, Player::X),
+        (4, Player::O),
+        (8, Player::X),
+        (2, Player::O),
+    ];
+    game_mock
+        .accept_with_preset("host", "guest", Some(preset))
+        .unwrap();
+
+    let game = game_mock
+        .query_games("host", "guest")
+        .unwrap()
+        .info
+        .current_game
+        .unwrap();
+    assert_eq!(game.turn(), Player::X);
+
+    // X blocks the diagonal, then O blocks the left column.
+    game_mock.play("host", 6).unwrap();
+    game_mock.play("guest", 3).unwrap();
+    let game = game_mock
+        .query_games("host", "guest")
+        .unwrap()
+        .info
+        .current_game
+        .unwrap();
+    assert_eq!(
+        game.board(),
+        &[
+            Player::X,
+            Player::None,
+            Player::O,
+            Player::O,
+            Player::O,
+            Player::None,
+            Player::X,
+            Player::None,
+            Player::X
+        ]
+    );
+}
+
+#[test]
+fn accept_invitation_with_illegal_preset() {
+    let mut game_mock = GameMock::new();
+    game_mock.invite("host", "guest").unwrap();
+
+    let preset = vec![(0, Player::X), (0, Player::O)];
+    let err = game_mock
+        .accept_with_preset("host", "guest", Some(preset))
+        .unwrap_err();
+    assert_eq!(ContractError::IllegalPreset, err.downcast().unwrap());
+
+    // The invitation is still pending.
+    assert!(
+        game_mock
+            .query_games("host", "guest")
+            .unwrap()
+            .info
+            .pending_invitation
+    );
+}
+
+#[test]
+fn no_pending_invitation() {
+    let mut game_mock = GameMock::new();
+    game_mock.invite("host", "guest").unwrap();
+    game_mock.reject("host", "guest").unwrap();
+
+    let err = game_mock.accept("host", "guest").unwrap_err();
+    assert_eq!(
+        ContractError::NoPendingInvitation {
+            host: "host".to_string(),
+            guest: "guest".to_string()
+        },
+        err.downcast().unwrap()
+    );
+}
+
+#[test]
+fn not_invited() {
+    let mut game_mock = GameMock::new();
+    game_mock.invite("host", "guest").unwrap();
+
+    let err = game_mock.accept("host", "intruder").unwrap_err();
+    assert_eq!(
+        ContractError::NotInvited {
+            host: "host".to_string(),
+            player: "intruder".to_string()
+        },
+        err.downcast().unwrap()
+    );
+
+    let err = game_mock.reject("host", "intruder").unwrap_err();
+    assert_eq!(
+        ContractError::NotInvited {
+            host: "host".to_string(),
+            player: "intruder".to_string()
+        },
+        err.downcast().unwrap()
+    );
+
+    // The invitation is still pending for the invited guest.
+    game_mock.accept("host", "guest").unwrap();
+}
+
+#[test]
+fn nicknames() {
+    let mut game_mock = GameMock::new();
+
+    let err = game_mock.set_nickname("host", " ").unwrap_err();
+    assert_eq!(
+        ContractError::InvalidNickname { max: 32 },
+        err.downcast().unwrap()
+    );
+    let err = game_mock.set_nickname("host", &"x".repeat(33)).unwrap_err();
+    assert_eq!(
+        ContractError::InvalidNickname { max: 32 },
+        err.downcast().unwrap()
+    );
+
+    let resp = game_mock.set_nickname("host", "Alice").unwrap();
+    let event = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+    assert_eq!(attribute!(event, "action"), "set_nickname");
+    assert_eq!(
+        game_mock.query_nickname("host").unwrap().nickname.unwrap(),
+        "Alice"
+    );
+    assert_eq!(game_mock.query_nickname("guest").unwrap().nickname, None);
+
+    // Only the players who set a nickname get one in the events.
+    let resp = game_mock.invite("host", "guest").unwrap();
+    let event = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+    assert_eq!(attribute!(event, "host_nickname"), "Alice");
+    assert!(!event
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "guest_nickname"));
+
+    game_mock.set_nickname("guest", "Bob").unwrap();
+    game_mock.accept("host", "guest").unwrap();
+    let resp = game_mock.play("host", 4).unwrap();
+    let event = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+    assert_eq!(attribute!(event, "host_nickname"), "Alice");
+    assert_eq!(attribute!(event, "guest_nickname"), "Bob");
+
+    let info = game_mock.query_games("host", "guest").unwrap().info;
+    assert_eq!(info.host_nickname.unwrap(), "Alice");
+    assert_eq!(info.guest_nickname.unwrap(), "Bob");
+}
+
+#[test]
+fn game_ref_in_events() {
+    let mut game_mock = GameMock::new();
+
+    let resp = game_mock.invite("host", "guest").unwrap();
+    let event = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+    let invite_ref = attribute!(event, "game_ref").to_string();
+    assert_eq!(invite_ref.len(), 8);
+
+    let resp = game_mock.accept("host", "guest").unwrap();
+    let event = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+    assert_eq!(attribute!(event, "game_ref"), invite_ref);
+
+    let resp = game_mock.play("host", 4).unwrap();
+    let event = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+    assert_eq!(attribute!(event, "game_ref"), invite_ref);
+
+    let info = game_mock.query_games("host", "guest").unwrap().info;
+    assert_eq!(info.game_ref, invite_ref);
+
+    // The next game between the same players gets another reference.
+    game_mock.resign("guest", "host", "guest").unwrap();
+    let resp = game_mock.invite("host", "guest").unwrap();
+    let event = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+    assert_ne!(attribute!(event, "game_ref"), invite_ref);
+    game_mock.accept("host", "guest").unwrap();
+
+    // The same players in the other order are another pair with its own references.
+    let resp = game_mock.invite("guest", "host").unwrap();
+    let event = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+    assert_eq!(
+        attribute!(event, "game_ref"),
+        game_ref(&Addr::unchecked("guest"), &Addr::unchecked("host"), 0)
+    );
+    assert_ne!(attribute!(event, "game_ref"), invite_ref);
+    assert_ne!(
+        game_ref(&Addr::unchecked("guest"), &Addr::unchecked("host"), 1),
+        game_ref(&Addr::unchecked("host"), &Addr::unchecked("guest"), 1)
+    );
+}
+
+#[test]
+fn guest_requests_symbol() {
+    let mut game_mock = GameMock::new();
+
+    // The host chose O, the guest gets the X they asked for and plays first.
+    game_mock
+        .invite_as("host", "guest", Some(Player::O))
+        .unwrap();
+    game_mock
+        .accept_as("host", "guest", Some(Player::X))
+        .unwrap();
+    let info = game_mock.query_games("host", "guest").unwrap().info;
+    assert_eq!(info.host_role, Player::O);
+    assert_eq!(info.guest_role, Player::X);
+    game_mock.play("guest", 4).unwrap();
+
+    // Without a choice from the host, the guest's request is honored.
+    game_mock.invite_as("host2", "guest", None).unwrap();
+    game_mock
+        .accept_as("host2", "guest", Some(Player::O))
+        .unwrap();
+    let info = game_mock.query_games("host2", "guest").unwrap().info;
+    assert_eq!(info.guest_role, Player::O);
+}
+
+#[test]
+fn winners_after_switching_symbols() {
+    let mut game_mock = GameMock::new();
+
+    // The host plays X and wins the first game.
+    game_mock
+        .invite_as("host", "guest", Some(Player::X))
+        .unwrap();
+    game_mock.accept_as("host", "guest", None).unwrap();
+    for (player, cell) in [
+        ("host", 0),
+        ("guest", 3),
+        ("host", 1),
+        ("guest", 4),
+        ("host", 2),
+    ] {
+        game_mock.play(player, cell).unwrap();
+    }
+
+    // The guest asks for X in the rematch and wins it.
+    game_mock.invite_as("host", "guest", None).unwrap();
+    game_mock
+        .accept_as("host", "guest", Some(Player::X))
+        .unwrap();
+    game_mock.play("guest", 4).unwrap();
+    game_mock.resign("host", "host", "guest").unwrap();
+
+    // The host goes back to X for the next invitation, the past games keep their winners.
+    game_mock
+        .invite_as("host", "guest", Some(Player::X))
+        .unwrap();
+    let info = game_mock.query_games("host", "guest").unwrap().info;
+    let winners: Vec<_> = info
+        .completed_results
+        .iter()
+        .map(|completed| completed.winner_addr.as_deref())
+        .collect();
+    assert_eq!(winners, vec![Some("host"), Some("guest")]);
+    assert_eq!(
+        game_mock.query_winner("host", "guest").unwrap(),
+        Some("guest".to_string())
+    );
+}
+
+#[test]
+fn guest_requests_conflicting_symbol() {
+    let mut game_mock = GameMock::new();
+    game_mock
+        .invite_as("host", "guest", Some(Player::X))
+        .unwrap();
+
+    let err = game_mock
+        .accept_as("host", "guest", Some(Player::X))
+        .unwrap_err();
+    assert_eq!(
+        ContractError::SymbolConflict { symbol: Player::X },
+        err.downcast().unwrap()
+    );
+
+    // The invitation is still pending and can be accepted as is.
+    game_mock.accept_as("host", "guest", None).unwrap();
+    let info = game_mock.query_games("host", "guest").unwrap().info;
+    assert_eq!(info.host_role, Player::X);
+}
+
+#[test]
+fn mutual_invitations() {
+    let mut game_mock = GameMock::new();
+    game_mock.invite("host", "guest").unwrap();
+
+    let resp = game_mock.invite("guest", "host").unwrap();
+    let event = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+    assert_eq!(attribute!(event, "action"), "mutual_accept");
+    assert_eq!(attribute!(event, "host"), "host");
+    assert_eq!(attribute!(event, "guest"), "guest");
+
+    let info = game_mock.query_games("host", "guest").unwrap().info;
+    assert_eq!(info.status, GameStatus::Active);
+    assert!(!info.pending_invitation);
+    // No entry was created for the reverse pair.
+    game_mock.query_games("guest", "host").unwrap_err();
+
+    game_mock.play("host", 4).unwrap();
+}
+
+#[test]
+fn mutual_invitations_terms() {
+    let mut game_mock = GameMock::new();
+    game_mock.invite_match("host", "guest", 3).unwrap();
+
+    // A counter-invitation with other terms is rejected rather than dropping them.
+    let terms_mismatch = ContractError::TermsMismatch {
+        host: "host".to_string(),
+        guest: "guest".to_string(),
+    };
+    let err = game_mock.invite_match("guest", "host", 2).unwrap_err();
+    assert_eq!(terms_mismatch, err.downcast().unwrap());
+    let err = game_mock
+        .invite_with_time_bank("guest", "host", 100)
+        .unwrap_err();
+    assert_eq!(terms_mismatch, err.downcast().unwrap());
+    let err = game_mock
+        .invite_with_message("guest", "host", "hi")
+        .unwrap_err();
+    assert_eq!(terms_mismatch, err.downcast().unwrap());
+    game_mock.query_games("guest", "host").unwrap_err();
+
+    // The same terms and a symbol the host didn't choose are accepted.
+    let info = game_mock.query_games("host", "guest").unwrap().info;
+    let symbol = info.host_role;
+    let resp = game_mock.invite_match("guest", "host", 3).unwrap();
+    let event = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+    assert_eq!(attribute!(event, "action"), "mutual_accept");
+    game_mock.concede_match("guest", "host", "guest").unwrap();
+
+    game_mock.invite("host", "guest").unwrap();
+    game_mock.invite_as("guest", "host", Some(symbol)).unwrap();
+    let info = game_mock.query_games("host", "guest").unwrap().info;
+    assert_eq!(info.guest_role, symbol);
+
+    // A symbol the host chose can't be taken.
+    let mut game_mock = GameMock::new();
+    game_mock
+        .invite_as("host", "guest", Some(Player::X))
+        .unwrap();
+    let err = game_mock
+        .invite_as("guest", "host", Some(Player::X))
+        .unwrap_err();
+    assert_eq!(
+        ContractError::SymbolConflict { symbol: Player::X },
+        err.downcast().unwrap()
+    );
+}
+
+#[test]
+fn role_independent_of_inviter() {
+    let mut game_mock = GameMock::new();
+    game_mock.invite("host", "guest").unwrap();
+    let info = game_mock.query_games("host", "guest").unwrap().info;
+
+    // The same pair with the roles of host and guest swapped.
+    let mut reverse_mock = GameMock::new();
+    reverse_mock.invite("guest", "host").unwrap();
+    let reverse_info = reverse_mock.query_games("guest", "host").unwrap().info;
+
+    assert_eq!(info.host_role, reverse_info.guest_role);
+    assert_eq!(info.guest_role, reverse_info.host_role);
+}
+
+#[test]
+fn reject_invitation() {
+    let mut game_mock = GameMock::new();
+    game_mock.invite("host", "guest").unwrap();
+
+    let resp = game_mock.reject("host", "guest").unwrap();
+    let event = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+    assert_eq!(attribute!(event, "action"), "reject_invite");
+    assert_eq!(attribute!(event, "host"), "host");
+    assert_eq!(attribute!(event, "guest"), "guest");
+
+    let resp = game_mock.query_games("host", "guest").unwrap();
+    assert_eq!(resp.info.host, "host");
+    assert_eq!(resp.info.guest, "guest");
+    assert!(!resp.info.pending_invitation);
+    assert_eq!(resp.info.current_game, None);
+}
+
+#[test]
+fn all_pairs() {
+    let mut game_mock = GameMock::new();
+    game_mock.init_game("host", "guest");
+    game_mock.invite("host", "other").unwrap();
+    game_mock.invite("alice", "bob").unwrap();
+
+    let resp = game_mock.query_all_pairs(None, Some(2)).unwrap();
+    assert_eq!(
+        vec![
+            ("host".to_string(), "guest".to_string()),
+            ("host".to_string(), "other".to_string())
+        ],
+        resp.pairs
+    );
+    assert_eq!(
+        resp.next_cursor,
+        Some(("host".to_string(), "other".to_string()))
+    );
+    let resp = game_mock
+        .query_all_pairs(Some(("host", "other")), None)
+        .unwrap();
+    assert_eq!(vec![("alice".to_string(), "bob".to_string())], resp.pairs);
+    assert_eq!(resp.next_cursor, None);
+}
+
+#[test]
+fn all_games_pages() {
+    let mut game_mock = GameMock::new();
+    for guest in ["guest1", "guest2", "guest3"] {
+        game_mock.invite("host", guest).unwrap();
+    }
+
+    let page = game_mock.query_all_games_page(None, 2).unwrap();
+    let guests: Vec<_> = page.games.iter().map(|info| info.guest.as_str()).collect();
+    assert_eq!(guests, vec!["guest1", "guest2"]);
+    assert_eq!(
+        page.next_cursor,
+        Some(("host".to_string(), "guest2".to_string()))
+    );
+
+    let page = game_mock.query_all_games_page(page.next_cursor, 2).unwrap();
+    let guests: Vec<_> = page.games.iter().map(|info| info.guest.as_str()).collect();
+    assert_eq!(guests, vec!["guest3"]);
+    assert_eq!(page.next_cursor, None);
+}
+
+#[test]
+fn invite_many() {
+    let mut game_mock = GameMock::new();
+    let guests: Vec<_> = (0..21).map(|i| format!("guest{i}")).collect();
+    let guests: Vec<_> = guests.iter().map(String::as_str).collect();
+    let err = game_mock.invite_many("host", &guests).unwrap_err();
+    assert_eq!(
+        ContractError::BatchTooLarge { max: 20 },
+        err.downcast().unwrap()
+    );
+
+    // A game is in progress with guest3, it is skipped.
+    game_mock.init_game("host", "guest3");
+    let resp = game_mock
+        .invite_many("host", &["guest0", "guest1", "guest2", "guest3"])
+        .unwrap();
+    let event = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+    assert_eq!(attribute!(event, "action"), "invite_many");
+    assert_eq!(attribute!(event, "invited"), "3");
+    assert_eq!(attribute!(event, "skipped"), "guest3");
+    let invites = resp.events.iter().filter(|ev| ev.ty == "wasm-invite");
+    assert_eq!(invites.count(), 3);
+
+    for guest in ["guest0", "guest1", "guest2"] {
+        assert_eq!(
+            game_mock.query_pending_invites_for(guest).unwrap().hosts,
+            vec!["host".to_string()]
+        );
+    }
+    assert_eq!(
+        game_mock.query_games("host", "guest3").unwrap().info.status,
+        GameStatus::Active
+    );
+}
+
+#[test]
+fn invite_cooldown() {
+    let mut game_mock = GameMock::instantiate_with(&InstantiateMsg {
+        admin: None,
+        invite_timeout: None,
+        move_timeout: None,
+        store_history: None,
+        max_active_per_player: None,
+        max_illegal_moves: None,
+        invite_cooldown: Some(60),
+    });
+    game_mock.invite("host", "guest").unwrap();
+    game_mock.reject("host", "guest").unwrap();
+
+    // The rejection does not reset the cooldown.
+    game_mock.advance_time(20);
+    let err = game_mock.invite("host", "guest").unwrap_err();
+    assert_eq!(
+        ContractError::InviteCooldown { remaining: 40 },
+        err.downcast().unwrap()
+    );
+    // Other guests can still be invited.
+    game_mock.invite("host", "other").unwrap();
+
+    game_mock.advance_time(40);
+    game_mock.invite("host", "guest").unwrap();
+}
+
+#[test]
+fn invite_with_message() {
+    let mut game_mock = GameMock::new();
+    let err = game_mock
+        .invite_with_message("host", "guest", &"x".repeat(257))
+        .unwrap_err();
+    assert_eq!(
+        ContractError::MessageTooLong { max: 256 },
+        err.downcast().unwrap()
+    );
+
+    let resp = game_mock
+        .invite_with_message("host", "guest", "rematch?")
+        .unwrap();
+    let event = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+    assert_eq!(attribute!(event, "invite_message"), "rematch?");
+    let info = game_mock.query_games("host", "guest").unwrap().info;
+    assert_eq!(info.invite_message, Some("rematch?".to_string()));
+
+    game_mock.accept("host", "guest").unwrap();
+    let info = game_mock.query_games("host", "guest").unwrap().info;
+    assert_eq!(info.invite_message, None);
+}
+
+#[test]
+fn reject_invitation_with_reason() {
+    let mut game_mock = GameMock::new();
+    game_mock.invite("host", "guest").unwrap();
+
+    let reason = "x".repeat(129);
+    let err = game_mock
+        .reject_with_reason("host", "guest", Some(&reason))
+        .unwrap_err();
+    assert_eq!(
+        ContractError::ReasonTooLong { max: 128 },
+        err.downcast().unwrap()
+    );
+
+    let resp = game_mock
+        .reject_with_reason("host", "guest", Some("busy right now"))
+        .unwrap();
+    let event = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+    assert_eq!(attribute!(event, "action"), "reject_invite");
+    assert_eq!(attribute!(event, "reason"), "busy right now");
+}
+
+#[test]
+fn invalid_reject() {
+    let mut game_mock = GameMock::new();
+    game_mock.init_game("host", "guest");
+
+    let err = game_mock.reject("host", "guest").unwrap_err();
+    assert_eq!(
+        ContractError::NoPendingInvitation {
+            host: "host".to_string(),
+            guest: "guest".to_string()
+        },
+        err.downcast().unwrap()
+    );
+}
+
+#[test]
+fn expire_invitation() {
+    let mut game_mock = GameMock::new();
+    game_mock.invite("host", "guest").unwrap();
+
+    let err = game_mock.expire_invite("host", "guest").unwrap_err();
+    assert_eq!(
+        ContractError::InvitationNotExpired {
+            host: "host".to_string(),
+            guest: "guest".to_string()
+        },
+        err.downcast().unwrap()
+    );
+
+    game_mock.advance_time(DEFAULT_INVITE_TIMEOUT);
+    let resp = game_mock.expire_invite("host", "guest").unwrap();
+    let event = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+    assert_eq!(attribute!(event, "action"), "expire_invite");
+    assert_eq!(attribute!(event, "host"), "host");
+    assert_eq!(attribute!(event, "guest"), "guest");
+
+    let resp = game_mock.query_games("host", "guest").unwrap();
+    assert!(!resp.info.pending_invitation);
+
+    // The expired invitation can no longer be accepted, but the host can invite again.
+    game_mock.accept("host", "guest").unwrap_err();
+    game_mock.init_game("host", "guest");
+}
+
+#[test]
+fn valid_play() {
+    let mut game_mock = GameMock::new();
+    game_mock.init_game("host", "guest");
+
+    // play
+    let resp = game_mock.play("host", 4).unwrap();
+
+    let event = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+    assert_eq!(attribute!(event, "action"), "play");
+    assert_eq!(attribute!(event, "host"), "host");
+    assert_eq!(attribute!(event, "guest"), "guest");
+    assert_eq!(attribute!(event, "cell"), "4");
+
+    let resp: GamesResponse = game_mock.query_games("host", "guest").unwrap();
+    assert_eq!(
+        GamesInfo {
+            host: "host".to_string(),
+            guest: "guest".to_string(),
+            host_nickname: None,
+            guest_nickname: None,
+            game_ref: game_ref(&Addr::unchecked("host"), &Addr::unchecked("guest"), 0),
+            host_role: Player::X,
+            guest_role: Player::O,
+            status: GameStatus::Active,
+            current_game: Some(Game {
+                board: [
+                    Player::None,
+                    Player::None,
+                    Player::None,
+                    Player::None,
+                    Player::X,
+                    Player::None,
+                    Player::None,
+                    Player::None,
+                    Player::None
+                ],
+                turn: Player::O,
+                created_at: Some(game_mock.block_time()),
+                completed_at: None,
+                last_move_at: Some(game_mock.block_time()),
+                abandoned: false,
+                abandoned_by: None,
+                host_role: None,
+            }),
+            pending_invitation: false,
+            invite_message: None,
+            completed_games: vec![],
+            completed_results: vec![],
+        },
+        resp.info
+    );
+}
+
+#[test]
+fn next_turn_attribute() {
+    let mut game_mock = GameMock::new();
+    game_mock.init_game("host", "guest");
+
+    let resp = game_mock.play("host", 0).unwrap();
+    let event = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+    assert_eq!(attribute!(event, "next_turn"), "guest");
+
+    let resp = game_mock.play("guest", 4).unwrap();
+    let event = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+    assert_eq!(attribute!(event, "next_turn"), "host");
+
+    game_mock.play("host", 1).unwrap();
+    game_mock.play("guest", 5).unwrap();
+
+    // The game ends with this move.
+    let resp = game_mock.play("host", 2).unwrap();
+    let event = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+    assert_eq!(attribute!(event, "next_turn"), "none");
+}
+
+#[test]
+fn play_sequence_full_game() {
+    let mut game_mock = GameMock::new();
+    // A bot playing against itself is responsible for every move.
+    game_mock.init_game("bot", "bot");
+
+    let resp = game_mock
+        .play_sequence("bot", "bot", "bot", vec![0, 3, 1, 4, 2])
+        .unwrap();
+    let event = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+    assert_eq!(attribute!(event, "action"), "play_sequence");
+    assert_eq!(attribute!(event, "moves"), "5");
+
+    let resp = game_mock.query_games("bot", "bot").unwrap();
+    assert_eq!(resp.info.current_game, None);
+    assert_eq!(resp.info.completed_games.len(), 1);
+    assert_eq!(
+        resp.info.completed_games[0].result(),
+        GameResult::Winner {
+            player: Player::X,
+            line: [0, 1, 2]
+        }
+    );
+
+    // Single moves can't tell which side the bot plays.
+    game_mock.init_game("bot", "bot");
+    let err = game_mock.play_in("bot", "bot", "bot", 0).unwrap_err();
+    assert_eq!(
+        ContractError::SelfPlay {
+            player: "bot".to_string()
+        },
+        err.downcast().unwrap()
+    );
+}
+
+#[test]
+fn play_sequence_reverts_on_opponent_move() {
+    let mut game_mock = GameMock::new();
+    game_mock.init_game("host", "guest");
+
+    // The second move belongs to the guest, so the whole sequence is rejected.
+    let err = game_mock
+        .play_sequence("host", "host", "guest", vec![0, 3])
+        .unwrap_err();
+    assert_eq!(
+        ContractError::GameError(GameError::NotYourTurn),
+        err.downcast().unwrap()
+    );
+
+    let resp = game_mock.query_games("host", "guest").unwrap();
+    assert_eq!(resp.info.current_game.unwrap().board(), &[Player::None; 9]);
+
+    // A sequence of a single move is fine.
+    game_mock
+        .play_sequence("host", "host", "guest", vec![0])
+        .unwrap();
+}
+
+#[test]
+fn play_sequence_not_involved() {
+    let mut game_mock = GameMock::new();
+    game_mock.init_game("host", "guest");
+
+    let err = game_mock
+        .play_sequence("player", "host", "guest", vec![0])
+        .unwrap_err();
+    assert_eq!(
+        ContractError::NotInvolved {
+            host: "host".to_string(),
+            guest: "guest".to_string(),
+            player: "player".to_string()
+        },
+        err.downcast().unwrap()
+    );
+}
+
+#[test]
+fn play_sequence_too_long() {
+    let mut game_mock = GameMock::new();
+    game_mock.init_game("host", "guest");
+
+    let err = game_mock
+        .play_sequence("host", "host", "guest", vec![0; 10])
+        .unwrap_err();
+    assert_eq!(
+        ContractError::CapacityExceeded {
+            resource: "moves".to_string()
+        },
+        err.downcast().unwrap()
+    );
+}
+
+#[test]
+fn play_sequence_empty() {
+    let mut game_mock = GameMock::new();
+    game_mock.init_game("host", "guest");
+    game_mock.play("host", 0).unwrap();
+
+    // The guest can't hold the game by sending no move.
+    game_mock.advance_time(DEFAULT_MOVE_TIMEOUT - 1);
+    let err = game_mock
+        .play_sequence("guest", "host", "guest", vec![])
+        .unwrap_err();
+    assert_eq!(ContractError::EmptySequence, err.downcast().unwrap());
+
+    game_mock.advance_time(1);
+    game_mock.claim_timeout("host", "host", "guest").unwrap();
+}
+
+#[test]
+fn play_nonce() {
+    let mut game_mock = GameMock::new();
+    game_mock.init_game("host", "guest");
+
+    game_mock
+        .play_with_nonce("host", "host", "guest", 4, 1)
+        .unwrap();
+    game_mock
+        .play_with_nonce("guest", "host", "guest", 0, 1)
+        .unwrap();
+
+    // The host's first move is rebroadcast on another cell.
+    let err = game_mock
+        .play_with_nonce("host", "host", "guest", 8, 1)
+        .unwrap_err();
+    assert_eq!(
+        ContractError::DuplicateNonce { nonce: 1 },
+        err.downcast().unwrap()
+    );
+    let game = game_mock
+        .query_games("host", "guest")
+        .unwrap()
+        .info
+        .current_game
+        .unwrap();
+    assert_eq!(game.board[8], Player::None);
+
+    game_mock
+        .play_with_nonce("host", "host", "guest", 8, 2)
+        .unwrap();
+}
+
+#[test]
+fn play_nonce_across_games() {
+    let mut game_mock = GameMock::new();
+    game_mock.invite_match("host", "guest", 2).unwrap();
+    game_mock.accept("host", "guest").unwrap();
+
+    for (player, cell, nonce) in [
+        ("host", 0, 1),
+        ("guest", 3, 1),
+        ("host", 1, 2),
+        ("guest", 4, 2),
+        ("host", 2, 3),
+    ] {
+        game_mock
+            .play_with_nonce(player, "host", "guest", cell, nonce)
+            .unwrap();
+    }
+
+    // The next game of the match started, the winning move can't be replayed in it.
+    let err = game_mock
+        .play_with_nonce("host", "host", "guest", 2, 3)
+        .unwrap_err();
+    assert_eq!(
+        ContractError::DuplicateNonce { nonce: 3 },
+        err.downcast().unwrap()
+    );
+    game_mock
+        .play_with_nonce("host", "host", "guest", 2, 4)
+        .unwrap();
+}
+
+#[test]
+fn illegal_moves_forfeit() {
+    let mut game_mock = GameMock::instantiate_with(&InstantiateMsg {
+        admin: None,
+        invite_timeout: None,
+        move_timeout: None,
+        store_history: None,
+        max_active_per_player: None,
+        max_illegal_moves: Some(2),
+        invite_cooldown: None,
+    });
+    game_mock.init_game("host", "guest");
+    game_mock.play("host", 4).unwrap();
+
+    // Illegal moves are recorded rather than rejected.
+    let resp = game_mock.play("guest", 4).unwrap();
+    let event = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+    assert_eq!(attribute!(event, "action"), "illegal_move");
+    assert_eq!(attribute!(event, "illegal_moves"), "1");
+    assert_eq!(attribute!(event, "forfeit"), "false");
+    game_mock.play("guest", 4).unwrap();
+
+    // A legal move resets the count.
+    game_mock.play("guest", 0).unwrap();
+    for _ in 0..2 {
+        game_mock.play("guest", 1).unwrap();
+    }
+    let resp = game_mock.play("guest", 1).unwrap();
+    let event = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+    assert_eq!(attribute!(event, "illegal_moves"), "3");
+    assert_eq!(attribute!(event, "forfeit"), "true");
+
+    let resp = game_mock.query_last_game("host", "guest").unwrap();
+    assert_eq!(
+        resp.result,
+        Some(GameResult::Abandoned {
+            by: Some(Player::O)
+        })
+    );
+}
+
+#[test]
+fn not_your_turn() {
+    let mut game_mock = GameMock::new();
+    game_mock.init_game("host", "guest");
+
+    // play
+    game_mock.play("host", 4).unwrap();
+
+    let err = game_mock.play("host", 5).unwrap_err();
+    assert_eq!(
+        ContractError::GameError(GameError::NotYourTurn),
+        err.downcast().unwrap()
+    );
+}
+
+#[test]
+fn invalid_cell() {
+    let mut game_mock = GameMock::new();
+    game_mock.init_game("host", "guest");
+
+    // play
+    let err = game_mock.play("host", 10).unwrap_err();
+    assert_eq!(
+        ContractError::GameError(GameError::CellOutOfBounds(10)),
+        err.downcast().unwrap()
+    );
+
+    // The cell is checked before the game is even loaded.
+    let err = game_mock.play_in("host", "host", "nobody", 9).unwrap_err();
+    assert_eq!(
+        ContractError::GameError(GameError::CellOutOfBounds(9)),
+        err.downcast().unwrap()
+    );
+
+    let game = game_mock
+        .query_games("host", "guest")
+        .unwrap()
+        .info
+        .current_game
+        .unwrap();
+    assert_eq!(game.board(), &[Player::None; 9]);
+    assert_eq!(game.turn(), Player::X);
+}
+
+#[test]
+fn play_at_coordinates() {
+    let mut game_mock = GameMock::new();
+    game_mock.init_game("host", "guest");
+
+    let resp = game_mock.play_at("host", 1, 1).unwrap();
+    let event = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+    assert_eq!(attribute!(event, "action"), "play");
+    assert_eq!(attribute!(event, "cell"), "4");
+
+    let game = game_mock
+        .query_games("host", "guest")
+        .unwrap()
+        .info
+        .current_game
+        .unwrap();
+    assert_eq!(game.board()[4], Player::X);
+
+    let err = game_mock.play_at("guest", 1, 3).unwrap_err();
+    assert_eq!(
+        ContractError::GameError(GameError::OutOfBounds { row: 1, col: 3 }),
+        err.downcast().unwrap()
+    );
+}
+
+#[test]
+fn cell_already_taken() {
+    let mut game_mock = GameMock::new();
+    game_mock.init_game("host", "guest");
+
+    game_mock.play("host", 4).unwrap();
+    let err = game_mock.play("guest", 4).unwrap_err();
+
+    assert_eq!(
+        ContractError::GameError(GameError::InvalidMove(4)),
+        err.downcast().unwrap()
+    );
+}
+
+#[test]
+fn game_not_found() {
+    let mut game_mock = GameMock::new();
+    let err = game_mock.play("host", 0).unwrap_err();
+
+    match err.downcast().unwrap() {
+        ContractError::StdError(StdError::NotFound { kind }) => {
+            assert!(kind.starts_with("type: tic_tac_toe::state::Games"))
+        }
+        err => panic!("unexpected error: {err}"),
+    }
+}
+
+#[test]
+fn play_after_game_finished() {
+    let mut game_mock = GameMock::new();
+    game_mock.invite("host", "guest").unwrap();
+
+    let err = game_mock.play("host", 0).unwrap_err();
+    assert_eq!(
+        ContractError::NoGameInProgress {
+            host: "host".to_string(),
+            guest: "guest".to_string()
+        },
+        err.downcast().unwrap()
+    );
+
+    game_mock.accept("host", "guest").unwrap();
+    game_mock.play("host", 0).unwrap();
+    game_mock.play("guest", 3).unwrap();
+    game_mock.play("host", 1).unwrap();
+    game_mock.play("guest", 4).unwrap();
+    game_mock.play("host", 2).unwrap();
+
+    let err = game_mock.play("guest", 5).unwrap_err();
+    assert_eq!(
+        ContractError::GameFinished {
+            host: "host".to_string(),
+            guest: "guest".to_string(),
+            completed: 1
+        },
+        err.downcast().unwrap()
+    );
+}
+
+#[test]
+fn player_not_in_game() {
+    let mut game_mock = GameMock::new();
+    game_mock.init_game("host", "guest");
+
+    let err = game_mock.play("player", 0).unwrap_err();
+    assert_eq!(
+        ContractError::NotInvolved {
+            player: "player".to_string(),
+            host: "host".to_string(),
+            guest: "guest".to_string()
+        },
+        err.downcast().unwrap()
+    );
+}
+
+#[test]
+fn best_of_three_match() {
+    let mut game_mock = GameMock::new();
+
+    let err = game_mock.invite_match("host", "guest", 0).unwrap_err();
+    assert_eq!(ContractError::InvalidMatchTarget, err.downcast().unwrap());
+
+    game_mock.invite_match("host", "guest", 2).unwrap();
+    game_mock.accept("host", "guest").unwrap();
+
+    // The host wins the first game, the next one starts right away.
+    for (player, cell) in [("host", 0), ("guest", 1), ("host", 3), ("guest", 5)] {
+        game_mock.play(player, cell).unwrap();
+    }
+    let resp = game_mock.play("host", 6).unwrap();
+    assert!(!resp.events.iter().any(|ev| ev.ty == "wasm-match_over"));
+
+    let resp = game_mock.query_games("host", "guest").unwrap();
+    assert_eq!(resp.info.status, GameStatus::Active);
+    assert_eq!(resp.info.completed_games.len(), 1);
+    assert_eq!(resp.info.current_game.unwrap().board, [Player::None; 9]);
+
+    // The host resigns the second game, giving the point to the guest.
+    game_mock.play("host", 4).unwrap();
+    game_mock.resign("host", "host", "guest").unwrap();
+    let resp = game_mock.query_games("host", "guest").unwrap();
+    assert_eq!(resp.info.status, GameStatus::Active);
+    assert_eq!(resp.info.completed_games.len(), 2);
+
+    // The host wins the third game and the match.
+    for (player, cell) in [("host", 0), ("guest", 1), ("host", 3), ("guest", 5)] {
+        game_mock.play(player, cell).unwrap();
+    }
+    let resp = game_mock.play("host", 6).unwrap();
+    let event = resp
+        .events
+        .iter()
+        .find(|ev| ev.ty == "wasm-match_over")
+        .unwrap();
+    assert_eq!(attribute!(event, "action"), "match_over");
+    assert_eq!(attribute!(event, "winner"), "host");
+    assert_eq!(attribute!(event, "host_score"), "2");
+    assert_eq!(attribute!(event, "guest_score"), "1");
+
+    let resp = game_mock.query_games("host", "guest").unwrap();
+    assert_eq!(resp.info.status, GameStatus::Completed);
+    assert_eq!(resp.info.current_game, None);
+    assert_eq!(resp.info.completed_games.len(), 3);
+    assert_eq!(game_mock.query_total_games().unwrap().total_completed, 3);
+}
+
+#[test]
+fn match_status() {
+    let mut game_mock = GameMock::new();
+    let status = |host_score, guest_score, match_winner: Option<&str>| MatchStatusResponse {
+        host_score,
+        guest_score,
+        target: Some(2),
+        match_over: match_winner.is_some(),
+        match_winner: match_winner.map(str::to_string),
+    };
+
+    // Players without a match get the defaults.
+    assert_eq!(
+        game_mock.query_match_status("host", "guest").unwrap(),
+        MatchStatusResponse {
+            host_score: 0,
+            guest_score: 0,
+            target: None,
+            match_over: false,
+            match_winner: None,
+        }
+    );
+
+    game_mock.invite_match("host", "guest", 2).unwrap();
+    game_mock.accept("host", "guest").unwrap();
+    assert_eq!(
+        game_mock.query_match_status("host", "guest").unwrap(),
+        status(0, 0, None)
+    );
+
+    for (player, cell) in [
+        ("host", 0),
+        ("guest", 1),
+        ("host", 3),
+        ("guest", 5),
+        ("host", 6),
+    ] {
+        game_mock.play(player, cell).unwrap();
+    }
+    assert_eq!(
+        game_mock.query_match_status("host", "guest").unwrap(),
+        status(1, 0, None)
+    );
+
+    game_mock.resign("host", "host", "guest").unwrap();
+    assert_eq!(
+        game_mock.query_match_status("host", "guest").unwrap(),
+        status(1, 1, None)
+    );
+
+    for (player, cell) in [
+        ("host", 0),
+        ("guest", 1),
+        ("host", 3),
+        ("guest", 5),
+        ("host", 6),
+    ] {
+        game_mock.play(player, cell).unwrap();
+    }
+    assert_eq!(
+        game_mock.query_match_status("host", "guest").unwrap(),
+        status(2, 1, Some("host"))
+    );
+}
+
+#[test]
+fn game_over_winner_x() {
+    let mut game_mock = GameMock::new();
+    game_mock.init_game("host", "guest");
+
+    // play
+    game_mock.play("host", 0).unwrap();
+    game_mock.play("guest", 1).unwrap();
+    game_mock.play("host", 3).unwrap();
+    game_mock.play("guest", 5).unwrap();
+    game_mock.play("host", 6).unwrap();
+
+    let resp = game_mock.query_games("host", "guest").unwrap();
+    let game = Game {
+        board: [
+            Player::X,
+            Player::O,
+            Player::None,
+            Player::X,
+            Player::None,
+            Player::O,
+            Player::X,
+            Player::None,
+            Player::None,
+        ],
+        turn: Player::O,
+        created_at: Some(game_mock.block_time()),
+        completed_at: Some(game_mock.block_time()),
+        last_move_at: Some(game_mock.block_time()),
+        abandoned: false,
+        abandoned_by: None,
+        host_role: Some(Player::X),
+    };
+
+    assert!(resp.info.completed_games[0].is_over());
+    assert_eq!(Player::X, resp.info.completed_games[0].winner().unwrap());
+    assert_eq!(
+        GamesResponse {
+            info: GamesInfo {
+                host: "host".to_string(),
+                guest: "guest".to_string(),
+                host_nickname: None,
+                guest_nickname: None,
+                game_ref: game_ref(&Addr::unchecked("host"), &Addr::unchecked("guest"), 1),
+                host_role: Player::X,
+                guest_role: Player::O,
+                status: GameStatus::Completed,
+                pending_invitation: false,
+                invite_message: None,
+                current_game: None,
+                completed_games: vec![game],
+                completed_results: vec![CompletedGameInfo {
+                    game,
+                    result: GameResult::Winner {
+                        player: Player::X,
+                        line: [0, 3, 6],
+                    },
+                    winner_addr: Some("host".to_string()),
+                    move_count: 5,
+                }],
+            },
+        },
+        resp
+    );
+}
+
+#[test]
+fn completed_results_winner_addr() {
+    let mut game_mock = GameMock::new();
+    game_mock.init_game("host", "guest");
+    for (player, cell) in [
+        ("host", 0),
+        ("guest", 3),
+        ("host", 1),
+        ("guest", 4),
+        ("host", 8),
+        ("guest", 5),
+    ] {
+        game_mock.play(player, cell).unwrap();
+    }
+
+    let info = game_mock.query_games("host", "guest").unwrap().info;
+    assert_eq!(info.guest_role, Player::O);
+    assert_eq!(
+        info.completed_results[0].winner_addr,
+        Some("guest".to_string())
+    );
+}
+
+#[test]
+fn game_timestamps() {
+    let mut game_mock = GameMock::new();
+    game_mock.init_game("host", "guest");
+    let created_at = game_mock.block_time();
+
+    game_mock.play("host", 0).unwrap();
+    game_mock.play("guest", 3).unwrap();
+    game_mock.play("host", 1).unwrap();
+    game_mock.play("guest", 4).unwrap();
+    game_mock.advance_time(60);
+    game_mock.play("host", 2).unwrap();
+
+    let game = game_mock
+        .query_last_game("host", "guest")
+        .unwrap()
+        .game
+        .unwrap();
+    assert_eq!(game.created_at, Some(created_at));
+    assert_eq!(game.completed_at, Some(created_at.plus_seconds(60)));
+}
+
+#[test]
+fn game_over_event() {
+    let mut game_mock = GameMock::new();
+    game_mock.init_game("host", "guest");
+    for (player, cell) in [("host", 0), ("guest", 3), ("host", 1), ("guest", 4)] {
+        let resp = game_mock.play(player, cell).unwrap();
+        assert!(!resp.events.iter().any(|ev| ev.ty == "wasm-game_over"));
+    }
+
+    let resp = game_mock.play("host", 2).unwrap();
+    let event = resp
+        .events
+        .iter()
+        .find(|ev| ev.ty == "wasm-game_over")
+        .unwrap();
+    assert_eq!(attribute!(event, "action"), "game_over");
+    assert_eq!(attribute!(event, "winner_symbol"), "X");
+    assert_eq!(attribute!(event, "winner_address"), "host");
+    assert_eq!(attribute!(event, "move_count"), "5");
+}
+
+#[test]
+fn game_over_with_draw() {
+    let mut game_mock = GameMock::new();
+    game_mock.init_game("host", "guest");
+
+    // play
+    game_mock.play("host", 0).unwrap();
+    game_mock.play("guest", 4).unwrap();
+    game_mock.play("host", 8).unwrap();
+    game_mock.play("guest", 3).unwrap();
+    game_mock.play("host", 5).unwrap();
+    game_mock.play("guest", 2).unwrap();
+    game_mock.play("host", 6).unwrap();
+    game_mock.play("guest", 7).unwrap();
+    let resp = game_mock.play("host", 1).unwrap();
+    let event = resp
+        .events
+        .iter()
+        .find(|ev| ev.ty == "wasm-game_over")
+        .unwrap();
+    assert_eq!(attribute!(event, "winner_symbol"), "none");
+    assert!(!event
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "winner_address"));
+
+    let resp = game_mock.query_games("host", "guest").unwrap();
+    let game = Game {
+        board: [
+            Player::X,
+            Player::X,
+            Player::O,
+            Player::O,
+            Player::O,
+            Player::X,
+            Player::X,
+            Player::O,
+            Player::X,
+        ],
+        turn: Player::O,
+        created_at: Some(game_mock.block_time()),
+        completed_at: Some(game_mock.block_time()),
+        last_move_at: Some(game_mock.block_time()),
+        abandoned: false,
+        abandoned_by: None,
+        host_role: Some(Player::X),
+    };
+    assert!(resp.info.completed_games[0].is_over());
+    assert!(resp.info.completed_games[0].winner().is_none());
+    assert_eq!(
+        GamesResponse {
+            info: GamesInfo {
+                host: "host".to_string(),
+                guest: "guest".to_string(),
+                host_nickname: None,
+                guest_nickname: None,
+                game_ref: game_ref(&Addr::unchecked("host"), &Addr::unchecked("guest"), 1),
+                host_role: Player::X,
+                guest_role: Player::O,
+                status: GameStatus::Completed,
+                pending_invitation: false,
+                invite_message: None,
+                current_game: None,
+                completed_games: vec![game],
+                completed_results: vec![CompletedGameInfo {
+                    game,
+                    result: GameResult::Draw,
+                    winner_addr: None,
+                    move_count: 9,
+                }],
+            },
+        },
+        resp
+    );
+}
+
+#[test]
+fn all_games_with_min_completed() {
+    let mut game_mock = GameMock::new();
+    game_mock.invite("host", "guest0").unwrap();
+    for (guest, completed) in [("guest1", 1), ("guest3", 3)] {
+        for _ in 0..completed {
+            game_mock.init_game("host", guest);
+            game_mock.resign(guest, "host", guest).unwrap();
+        }
+    }
+
+    let guests = |min_completed| {
+        game_mock
+            .query_all_games_with_completed(min_completed)
+            .unwrap()
+            .games
+            .into_iter()
+            .map(|info| info.guest)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(guests(2), vec!["guest3".to_string()]);
+    assert_eq!(guests(1), vec!["guest1".to_string(), "guest3".to_string()]);
+    assert_eq!(guests(0).len(), 3);
+}
+
+#[test]
+fn games_as_guest() {
+    let mut game_mock = GameMock::new();
+    game_mock.init_game("alice", "player");
+    game_mock.invite("bob", "player").unwrap();
+    game_mock.invite("player", "carol").unwrap();
+
+    let pairs: Vec<_> = game_mock
+        .query_games_as_guest("player")
+        .unwrap()
+        .games
+        .into_iter()
+        .map(|info| (info.host, info.guest))
+        .collect();
+    assert_eq!(
+        pairs,
+        vec![
+            ("bob".to_string(), "player".to_string()),
+            ("alice".to_string(), "player".to_string())
+        ]
+    );
+}
+
+#[test]
+fn games_by_result() {
+    let mut game_mock = GameMock::new();
+
+    // X wins between host and guest.
+    game_mock.init_game("host", "guest");
+    for (player, cell) in [
+        ("host", 0),
+        ("guest", 3),
+        ("host", 1),
+        ("guest", 4),
+        ("host", 2),
+    ] {
+        game_mock.play(player, cell).unwrap();
+    }
+    // A draw between alice and bob.
+    game_mock
+        .invite_as("alice", "bob", Some(Player::X))
+        .unwrap();
+    game_mock.accept("alice", "bob").unwrap();
+    for (player, cell) in [
+        ("alice", 0),
+        ("bob", 4),
+        ("alice", 8),
+        ("bob", 1),
+        ("alice", 7),
+        ("bob", 6),
+        ("alice", 2),
+        ("bob", 5),
+        ("alice", 3),
+    ] {
+        game_mock.play_in(player, "alice", "bob", cell).unwrap();
+    }
+    // carol resigns against dave.
+    game_mock.init_game("carol", "dave");
+    game_mock.resign("carol", "carol", "dave").unwrap();
+
+    let games = |result| {
+        game_mock
+            .query_games_by_result(result)
+            .unwrap()
+            .games
+            .into_iter()
+            .map(|info| {
+                let results: Vec<_> = info
+                    .completed_results
+                    .iter()
+                    .map(|completed| completed.result)
+                    .collect();
+                (info.host, results)
+            })
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        games(ResultFilter::Draw),
+        vec![("alice".to_string(), vec![GameResult::Draw])]
+    );
+    assert_eq!(
+        games(ResultFilter::Winner(Player::X)),
+        vec![(
+            "host".to_string(),
+            vec![GameResult::Winner {
+                player: Player::X,
+                line: [0, 1, 2]
+            }]
+        )]
+    );
+    assert_eq!(games(ResultFilter::Winner(Player::O)), vec![]);
+    let abandoned = games(ResultFilter::Abandoned);
+    assert_eq!(abandoned.len(), 1);
+    assert_eq!(abandoned[0].0, "carol");
+}
+
+#[test]
+fn games_by_status() {
+    let mut game_mock = GameMock::new();
+
+    // A completed game between host and guest.
+    game_mock.init_game("host", "guest");
+    game_mock.play("host", 0).unwrap();
+    game_mock.play("guest", 3).unwrap();
+    game_mock.play("host", 1).unwrap();
+    game_mock.play("guest", 4).unwrap();
+    game_mock.play("host", 2).unwrap();
+    // A pending invitation between host and guest2.
+    game_mock.invite("host", "guest2").unwrap();
+    // An active game between host2 and guest.
+    game_mock.init_game("host2", "guest");
+
+    let pairs = |status| {
+        game_mock
+            .query_games_by_status(status)
+            .unwrap()
+            .games
+            .into_iter()
+            .map(|info| (info.host, info.guest, info.status))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        pairs(GameStatus::Pending),
+        vec![(
+            "host".to_string(),
+            "guest2".to_string(),
+            GameStatus::Pending
+        )]
+    );
+    assert_eq!(
+        pairs(GameStatus::Active),
+        vec![("host2".to_string(), "guest".to_string(), GameStatus::Active)]
+    );
+    assert_eq!(
+        pairs(GameStatus::Completed),
+        vec![(
+            "host".to_string(),
+            "guest".to_string(),
+            GameStatus::Completed
+        )]
+    );
+}
+
+#[test]
+fn last_game() {
+    let mut game_mock = GameMock::new();
+
+    // No game has been played yet.
+    let resp = game_mock.query_last_game("host", "guest").unwrap();
+    assert_eq!(
+        LastGameResponse {
+            game: None,
+            result: None
+        },
+        resp
+    );
+
+    // The first game is won by X.
+    game_mock.init_game("host", "guest");
+    game_mock.play("host", 0).unwrap();
+    game_mock.play("guest", 3).unwrap();
+    game_mock.play("host", 1).unwrap();
+    game_mock.play("guest", 4).unwrap();
+    game_mock.play("host", 2).unwrap();
+
+    // The second game is a draw.
+    game_mock.init_game("host", "guest");
+    game_mock.play("host", 0).unwrap();
+    game_mock.play("guest", 4).unwrap();
+    game_mock.play("host", 8).unwrap();
+    game_mock.play("guest", 3).unwrap();
+    game_mock.play("host", 5).unwrap();
+    game_mock.play("guest", 2).unwrap();
+    game_mock.play("host", 6).unwrap();
+    game_mock.play("guest", 7).unwrap();
+    game_mock.play("host", 1).unwrap();
+
+    let resp = game_mock.query_last_game("host", "guest").unwrap();
+    let completed = game_mock
+        .query_games("host", "guest")
+        .unwrap()
+        .info
+        .completed_games;
+    assert_eq!(completed.len(), 2);
+    assert_eq!(resp.game, Some(completed[1]));
+    assert_eq!(resp.result, Some(GameResult::Draw));
+}
+
+#[test]
+fn total_games() {
+    let mut game_mock = GameMock::new();
+    assert_eq!(game_mock.query_total_games().unwrap().total_completed, 0);
+
+    game_mock.init_game("host", "guest");
+    game_mock.play("host", 0).unwrap();
+    game_mock.play("guest", 3).unwrap();
+    game_mock.play("host", 1).unwrap();
+    game_mock.play("guest", 4).unwrap();
+    game_mock.play("host", 2).unwrap();
+    assert_eq!(game_mock.query_total_games().unwrap().total_completed, 1);
+
+    // An unfinished game is not counted.
+    game_mock.init_game("bot", "bot");
+    game_mock
+        .play_sequence("bot", "bot", "bot", vec![0, 3, 1, 4])
+        .unwrap();
+    assert_eq!(game_mock.query_total_games().unwrap().total_completed, 1);
+
+    game_mock
+        .play_sequence("bot", "bot", "bot", vec![2])
+        .unwrap();
+    assert_eq!(game_mock.query_total_games().unwrap().total_completed, 2);
+}
+
+#[test]
+fn suggest_move() {
+    let mut game_mock = GameMock::new();
+    game_mock.init_game("host", "guest");
+
+    // X threatens the top row, O must block it.
+    game_mock.play("host", 0).unwrap();
+    game_mock.play("guest", 4).unwrap();
+    game_mock.play("host", 1).unwrap();
+    let resp = game_mock.query_suggest_move("host", "guest").unwrap();
+    assert_eq!(resp, SuggestMoveResponse { cell: 2 });
+
+    // Once the game is over, there is nothing to suggest.
+    game_mock.play("guest", 8).unwrap();
+    game_mock.play("host", 2).unwrap();
+    game_mock.query_suggest_move("host", "guest").unwrap_err();
+}
+
+#[test]
+fn completed_game_at_index() {
+    let mut game_mock = GameMock::new();
+    for player in ["host", "guest", "host"] {
+        game_mock.init_game("host", "guest");
+        game_mock.resign(player, "host", "guest").unwrap();
+    }
+
+    let resp = game_mock.query_completed_game("host", "guest", 1).unwrap();
+    assert_eq!(
+        resp.result,
+        GameResult::Abandoned {
+            by: Some(Player::O)
+        }
+    );
+    assert_eq!(
+        resp.game,
+        game_mock
+            .query_games("host", "guest")
+            .unwrap()
+            .info
+            .completed_games[1]
+    );
+}
+
+#[test]
+fn completed_game_out_of_range() {
+    let mut game_mock = GameMock::new();
+    game_mock.init_game("host", "guest");
+    game_mock.resign("host", "host", "guest").unwrap();
+
+    game_mock.query_completed_game("host", "guest", 0).unwrap();
+    let err = game_mock
+        .query_completed_game("host", "guest", 1)
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("No completed game at index 1, only 1 game(s) completed"));
+}
+
+#[test]
+fn board_rows() {
+    let mut game_mock = GameMock::new();
+    game_mock.init_game("host", "guest");
+    game_mock.play("host", 4).unwrap();
+
+    let rows = game_mock.query_board_rows("host", "guest").unwrap();
+    assert_eq!(
+        rows,
+        vec![
+            vec![Player::None; 3],
+            vec![Player::None, Player::X, Player::None],
+            vec![Player::None; 3],
+        ]
+    );
+}
+
+#[test]
+fn evaluate_position() {
+    let mut game_mock = GameMock::new();
+    game_mock.init_game("host", "guest");
+
+    // The empty board is a dead draw.
+    let resp = game_mock.query_evaluate_position("host", "guest").unwrap();
+    assert_eq!(resp.best_outcome, GameResult::Draw);
+    assert!(resp.best_move.is_some());
+
+    // O answering the corner opening on an edge loses by force.
+    game_mock.play("host", 0).unwrap();
+    game_mock.play("guest", 1).unwrap();
+    let resp = game_mock.query_evaluate_position("host", "guest").unwrap();
+    assert!(matches!(
+        resp.best_outcome,
+        GameResult::Winner {
+            player: Player::X,
+            ..
+        }
+    ));
+
+    // There is nothing to evaluate once the game is over.
+    for (player, cell) in [("host", 3), ("guest", 4), ("host", 6)] {
+        game_mock.play(player, cell).unwrap();
+    }
+    game_mock
+        .query_evaluate_position("host", "guest")
+        .unwrap_err();
+}
+
+#[test]
+fn odds() {
+    let mut game_mock = GameMock::new();
+    game_mock.init_game("host", "guest");
+
+    let resp = game_mock.query_odds("host", "guest").unwrap();
+    assert_eq!(resp.x_win + resp.o_win + resp.draw, 1000);
+
+    // X threatens the top row, it wins if O does not block it.
+    for (player, cell) in [("host", 0), ("guest", 3), ("host", 1)] {
+        game_mock.play(player, cell).unwrap();
+    }
+    let resp = game_mock.query_odds("host", "guest").unwrap();
+    assert_eq!(resp.x_win + resp.o_win + resp.draw, 1000);
+    assert!(resp.x_win > 500);
+}
+
+#[test]
+fn threats() {
+    let mut game_mock = GameMock::new();
+    game_mock.init_game("host", "guest");
+
+    for (player, cell) in [("host", 4), ("guest", 0), ("host", 8)] {
+        game_mock.play(player, cell).unwrap();
+    }
+    // Every line through the top-left corner is blocked for X.
+    assert_eq!(
+        game_mock.query_threats("host", "guest", Player::X).unwrap(),
+        vec![[3, 4, 5], [6, 7, 8], [1, 4, 7], [2, 5, 8], [2, 4, 6]]
+    );
+    assert_eq!(
+        game_mock.query_threats("host", "guest", Player::O).unwrap(),
+        vec![[0, 1, 2], [0, 3, 6]]
+    );
+}
+
+#[test]
+fn empty_count() {
+    let mut game_mock = GameMock::new();
+    assert_eq!(game_mock.query_empty_count("host", "guest").unwrap(), 0);
+    game_mock.init_game("host", "guest");
+    assert_eq!(game_mock.query_empty_count("host", "guest").unwrap(), 9);
+
+    for (i, (player, cell)) in [("host", 0), ("guest", 3), ("host", 1), ("guest", 4)]
+        .into_iter()
+        .enumerate()
+    {
+        game_mock.play(player, cell).unwrap();
+        assert_eq!(game_mock.query_empty_count("host", "guest").unwrap(), 8 - i);
+    }
+
+    // No game is in progress once it is over.
+    game_mock.play("host", 2).unwrap();
+    assert_eq!(game_mock.query_empty_count("host", "guest").unwrap(), 0);
+}
+
+#[test]
+fn packed_board() {
+    let mut game_mock = GameMock::new();
+    game_mock.init_game("host", "guest");
+    game_mock.play("host", 4).unwrap();
+    game_mock.play("guest", 0).unwrap();
+
+    let packed = game_mock.query_packed_board("host", "guest").unwrap();
+    assert_eq!(packed, 0b01_00_00_00_10);
+    let game = game_mock
+        .query_games("host", "guest")
+        .unwrap()
+        .info
+        .current_game;
+    assert_eq!(Game::unpack(packed).unwrap().board, game.unwrap().board);
+}
+
+#[test]
+fn hint() {
+    let mut game_mock = GameMock::new();
+    game_mock.init_game("host", "guest");
+    assert_eq!(game_mock.query_hint("host", "guest").unwrap(), None);
+
+    // O has to block the top row.
+    for (player, cell) in [("host", 0), ("guest", 4), ("host", 1)] {
+        game_mock.play(player, cell).unwrap();
+    }
+    assert_eq!(game_mock.query_hint("host", "guest").unwrap(), Some(2));
+
+    // X wins on the left column rather than block the right column.
+    for (player, cell) in [("guest", 2), ("host", 6), ("guest", 8)] {
+        game_mock.play(player, cell).unwrap();
+    }
+    assert_eq!(game_mock.query_hint("host", "guest").unwrap(), Some(3));
+}
+
+#[test]
+fn chat() {
+    let mut game_mock = GameMock::new();
+    game_mock.init_game("host", "guest");
+
+    game_mock
+        .post_message("host", "host", "guest", "good luck")
+        .unwrap();
+    game_mock
+        .post_message("guest", "host", "guest", "you'll need it")
+        .unwrap();
+    assert_eq!(
+        game_mock.query_chat("host", "guest").unwrap(),
+        vec![
+            (Addr::unchecked("host"), "good luck".to_string()),
+            (Addr::unchecked("guest"), "you'll need it".to_string()),
+        ]
+    );
+
+    let err = game_mock
+        .post_message("player", "host", "guest", "hello")
+        .unwrap_err();
+    assert_eq!(
+        ContractError::NotInvolved {
+            host: "host".to_string(),
+            guest: "guest".to_string(),
+            player: "player".to_string()
+        },
+        err.downcast().unwrap()
+    );
+
+    let err = game_mock
+        .post_message("host", "host", "guest", &"a".repeat(281))
+        .unwrap_err();
+    assert_eq!(
+        ContractError::InvalidMessage { max: 280 },
+        err.downcast().unwrap()
+    );
+
+    // Only the most recent messages are kept.
+    for i in 0..20 {
+        game_mock
+            .post_message("host", "host", "guest", &i.to_string())
+            .unwrap();
+    }
+    let chat = game_mock.query_chat("host", "guest").unwrap();
+    assert_eq!(chat.len(), 20);
+    assert_eq!(chat[0].1, "0");
+}
+
+#[test]
+fn next_player() {
+    let mut game_mock = GameMock::new();
+    game_mock.invite("host", "guest").unwrap();
+    let err = game_mock.query_next_player("host", "guest").unwrap_err();
+    assert!(err.to_string().contains(
+        &ContractError::NoGameInProgress {
+            host: "host".to_string(),
+            guest: "guest".to_string()
+        }
+        .to_string()
+    ));
+
+    game_mock.accept("host", "guest").unwrap();
+    game_mock.play("host", 4).unwrap();
+    assert_eq!(
+        game_mock.query_next_player("host", "guest").unwrap(),
+        NextPlayerResponse {
+            symbol: Player::O,
+            address: "guest".to_string()
+        }
+    );
+}
+
+#[test]
+fn winner() {
+    let mut game_mock = GameMock::new();
+    game_mock.query_winner("host", "guest").unwrap_err();
+
+    // The guest plays X and wins.
+    game_mock
+        .invite_as("host", "guest", Some(Player::O))
+        .unwrap();
+    game_mock.accept("host", "guest").unwrap();
+    for (player, cell) in [("guest", 0), ("host", 3), ("guest", 1)] {
+        game_mock.play(player, cell).unwrap();
+    }
+    assert_eq!(game_mock.query_winner("host", "guest").unwrap(), None);
+
+    for (player, cell) in [("host", 4), ("guest", 2)] {
+        game_mock.play(player, cell).unwrap();
+    }
+    assert_eq!(
+        game_mock.query_winner("host", "guest").unwrap(),
+        Some("guest".to_string())
+    );
+}
+
+#[test]
+fn my_symbol() {
+    let mut game_mock = GameMock::new();
+    game_mock
+        .invite_as("host", "guest", Some(Player::O))
+        .unwrap();
+
+    assert_eq!(
+        game_mock.query_my_symbol("host", "guest", "host").unwrap(),
+        Player::O
+    );
+    assert_eq!(
+        game_mock.query_my_symbol("host", "guest", "guest").unwrap(),
+        Player::X
+    );
+    let err = game_mock
+        .query_my_symbol("host", "guest", "player")
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("The player player is not involved in a game between host and guest"));
+}
+
+#[test]
+fn can_play() {
+    let mut game_mock = GameMock::new();
+    game_mock.init_game("host", "guest");
+
+    assert_eq!(
+        game_mock
+            .query_can_play("host", "guest", "host", 4)
+            .unwrap(),
+        CanPlayResponse {
+            allowed: true,
+            reason: None
+        }
+    );
+    // Nothing was played.
+    game_mock.play("host", 4).unwrap();
+
+    assert_eq!(
+        game_mock
+            .query_can_play("host", "guest", "guest", 4)
+            .unwrap(),
+        CanPlayResponse {
+            allowed: false,
+            reason: Some(GameError::InvalidMove(4).to_string())
+        }
+    );
+    assert_eq!(
+        game_mock
+            .query_can_play("host", "guest", "host", 0)
+            .unwrap(),
+        CanPlayResponse {
+            allowed: false,
+            reason: Some(GameError::NotYourTurn.to_string())
+        }
+    );
+    assert_eq!(
+        game_mock
+            .query_can_play("host", "guest", "player", 0)
+            .unwrap(),
+        CanPlayResponse {
+            allowed: false,
+            reason: Some(GameError::InvalidPlayer.to_string())
+        }
+    );
+}
+
+#[test]
+fn move_preview() {
+    let mut game_mock = GameMock::new();
+    game_mock.init_game("host", "guest");
+    assert_eq!(
+        game_mock.query_move_preview("host", "guest", 4).unwrap(),
+        MovePreviewResponse {
+            legal: true,
+            ends_game: false,
+            result_if_played: Some(GameResult::InProgress)
+        }
+    );
+
+    for (player, cell) in [("host", 0), ("guest", 3), ("host", 1), ("guest", 4)] {
+        game_mock.play(player, cell).unwrap();
+    }
+    assert_eq!(
+        game_mock.query_move_preview("host", "guest", 2).unwrap(),
+        MovePreviewResponse {
+            legal: true,
+            ends_game: true,
+            result_if_played: Some(GameResult::Winner {
+                player: Player::X,
+                line: [0, 1, 2]
+            })
+        }
+    );
+    assert_eq!(
+        game_mock.query_move_preview("host", "guest", 0).unwrap(),
+        MovePreviewResponse {
+            legal: false,
+            ends_game: false,
+            result_if_played: None
+        }
+    );
+
+    // The last empty cell fills the board without a winner.
+    for (player, cell) in [("host", 5), ("guest", 2), ("host", 6), ("guest", 8)] {
+        game_mock.play(player, cell).unwrap();
+    }
+    assert_eq!(
+        game_mock.query_move_preview("host", "guest", 7).unwrap(),
+        MovePreviewResponse {
+            legal: true,
+            ends_game: true,
+            result_if_played: Some(GameResult::Draw)
+        }
+    );
+    // Nothing was played.
+    assert!(game_mock
+        .query_games("host", "guest")
+        .unwrap()
+        .info
+        .current_game
+        .is_some());
+}
+
+#[test]
+fn validate_game() {
+    let mut game_mock = GameMock::new();
+    game_mock.invite("host", "guest").unwrap();
+    game_mock.query_validate_game("host", "guest").unwrap_err();
+
+    game_mock.accept("host", "guest").unwrap();
+    game_mock.play("host", 4).unwrap();
+    assert_eq!(
+        game_mock.query_validate_game("host", "guest").unwrap(),
+        ValidateGameResponse {
+            turn: Player::O,
+            expected_turn: Player::O,
+            valid: true,
+        }
+    );
+}
+
+#[test]
+fn pending_invites_for() {
+    let mut game_mock = GameMock::new();
+    game_mock.invite("host1", "guest").unwrap();
+    game_mock.invite("host2", "guest").unwrap();
+    // Neither an accepted invitation nor an invitation for another guest are listed.
+    game_mock.init_game("host3", "guest");
+    game_mock.invite("host1", "other").unwrap();
+
+    let resp = game_mock.query_pending_invites_for("guest").unwrap();
+    assert_eq!(
+        PendingInvitesResponse {
+            hosts: vec!["host1".to_string(), "host2".to_string()]
+        },
+        resp
+    );
+
+    let resp = game_mock.query_pending_invites_for("nobody").unwrap();
+    assert!(resp.hosts.is_empty());
+}
+
+#[test]
+fn accept_invitations_of_busy_host() {
+    let mut game_mock = GameMock::instantiate_with(&InstantiateMsg {
+        admin: None,
+        invite_timeout: None,
+        move_timeout: None,
+        store_history: None,
+        max_active_per_player: Some(1),
+        max_illegal_moves: None,
+        invite_cooldown: None,
+    });
+    game_mock.invite("host", "alice").unwrap();
+    game_mock.invite("host", "bob").unwrap();
+
+    // The first guest to accept gets the game, the host is then at the limit.
+    game_mock.accept("host", "bob").unwrap();
+    let err = game_mock.accept("host", "alice").unwrap_err();
+    assert_eq!(
+        ContractError::TooManyActiveGames {
+            player: "host".to_string(),
+            limit: 1
+        },
+        err.downcast().unwrap()
+    );
+    // The invitation is kept and can be accepted once the host is free again.
+    let info = game_mock.query_games("host", "alice").unwrap().info;
+    assert_eq!(info.status, GameStatus::Pending);
+
+    game_mock.resign("bob", "host", "bob").unwrap();
+    game_mock.accept("host", "alice").unwrap();
+    let err = game_mock.accept("host", "alice").unwrap_err();
+    assert_eq!(
+        ContractError::NoPendingInvitation {
+            host: "host".to_string(),
+            guest: "alice".to_string()
+        },
+        err.downcast().unwrap()
+    );
+}
+
+#[test]
+fn max_active_per_player() {
+    let mut game_mock = GameMock::instantiate_with(&InstantiateMsg {
+        admin: None,
+        invite_timeout: None,
+        move_timeout: None,
+        store_history: None,
+        max_active_per_player: Some(1),
+        max_illegal_moves: None,
+        invite_cooldown: None,
+    });
+    game_mock.init_game("host", "guest");
+
+    // Both the host and the guest are at the limit.
+    game_mock.invite("host", "other").unwrap();
+    let err = game_mock.accept("host", "other").unwrap_err();
+    assert_eq!(
+        ContractError::TooManyActiveGames {
+            player: "host".to_string(),
+            limit: 1
+        },
+        err.downcast().unwrap()
+    );
+    game_mock.invite("other", "guest").unwrap();
+    let err = game_mock.accept("other", "guest").unwrap_err();
+    assert_eq!(
+        ContractError::TooManyActiveGames {
+            player: "guest".to_string(),
+            limit: 1
+        },
+        err.downcast().unwrap()
+    );
+
+    // A new game can be started once the first one is over.
+    game_mock.resign("guest", "host", "guest").unwrap();
+    game_mock.accept("host", "other").unwrap();
+
+    // A seat handed over frees the player who left it, and takes the place of the one who sits on it.
+    game_mock
+        .transfer_seat("other", "host", "other", "newcomer")
+        .unwrap();
+    game_mock.accept("other", "guest").unwrap();
+    game_mock.invite("newcomer", "player").unwrap();
+    let err = game_mock.accept("newcomer", "player").unwrap_err();
+    assert_eq!(
+        ContractError::TooManyActiveGames {
+            player: "newcomer".to_string(),
+            limit: 1
+        },
+        err.downcast().unwrap()
+    );
+
+    // A purged game is not in progress anymore.
+    game_mock
+        .purge_pair("Owner", "host", "newcomer", true)
+        .unwrap();
+    game_mock.accept("newcomer", "player").unwrap();
+
+    // A seat can't be handed over to a player at the limit.
+    let err = game_mock
+        .transfer_seat("player", "newcomer", "player", "guest")
+        .unwrap_err();
+    assert_eq!(
+        ContractError::TooManyActiveGames {
+            player: "guest".to_string(),
+            limit: 1
+        },
+        err.downcast().unwrap()
+    );
+}
+
+#[test]
+fn pair_exists() {
+    let mut game_mock = GameMock::new();
+    assert!(!game_mock.query_pair_exists("host", "guest").unwrap());
+
+    game_mock.invite("host", "guest").unwrap();
+    assert!(game_mock.query_pair_exists("host", "guest").unwrap());
+    // Pairs are stored under the host and guest of the invitation.
+    assert!(!game_mock.query_pair_exists("guest", "host").unwrap());
+}
+
+#[test]
+fn games_batch() {
+    let mut game_mock = GameMock::new();
+    game_mock.init_game("host1", "guest");
+    game_mock.invite("host2", "guest").unwrap();
+
+    let resp = game_mock
+        .query_games_batch(&[("host2", "guest"), ("host3", "guest"), ("host1", "guest")])
+        .unwrap();
+    assert_eq!(resp.len(), 3);
+    let info = resp[0].as_ref().unwrap();
+    assert_eq!(info.host, "host2");
+    assert_eq!(info.status, GameStatus::Pending);
+    assert_eq!(resp[1], None);
+    let info = resp[2].as_ref().unwrap();
+    assert_eq!(info.host, "host1");
+    assert_eq!(info.status, GameStatus::Active);
+
+    let err = game_mock
+        .query_games_batch(&[("host1", "guest"); 21])
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("At most 20 pairs of players can be queried at once"));
+}
+
+#[test]
+fn storage_key() {
+    let mut game_mock = GameMock::new();
+    game_mock.init_game("host", "guest");
+
+    let key = game_mock.query_storage_key("host", "guest").unwrap();
+    assert_eq!(game_mock.query_storage_key("guest", "host").unwrap(), key);
+
+    // The key points to the stored games.
+    let raw_key = GAMES.key((&Addr::unchecked("host"), &Addr::unchecked("guest")));
+    let hex: String = raw_key.iter().map(|byte| format!("{byte:02x}")).collect();
+    assert_eq!(key, hex);
+    let raw = game_mock
+        .app
+        .wrap()
+        .query_wasm_raw(game_mock.contract_addr.clone(), raw_key.to_vec())
+        .unwrap();
+    assert!(raw.is_some());
+}
+
+#[test]
+fn active_opponents() {
+    let mut game_mock = GameMock::new();
+    game_mock.init_game("player", "guest1");
+    game_mock.init_game("host2", "player");
+    // Neither a finished game nor a pending invitation are listed.
+    game_mock.init_game("player", "guest3");
+    game_mock.resign("guest3", "player", "guest3").unwrap();
+    game_mock.invite("player", "guest4").unwrap();
+
+    assert_eq!(
+        game_mock.query_active_opponents("player").unwrap(),
+        vec!["host2".to_string(), "guest1".to_string()]
+    );
+    assert!(game_mock
+        .query_active_opponents("nobody")
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn transfer_seat() {
+    let mut game_mock = GameMock::new();
+    game_mock.init_game("host", "guest");
+    game_mock.play("host", 0).unwrap();
+    let host_role = game_mock
+        .query_games("host", "guest")
+        .unwrap()
+        .info
+        .host_role;
+
+    let resp = game_mock
+        .transfer_seat("host", "host", "guest", "wallet")
+        .unwrap();
+    let event = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+    assert_eq!(attribute!(event, "action"), "transfer_seat");
+    assert_eq!(attribute!(event, "host"), "wallet");
+    assert_eq!(attribute!(event, "guest"), "guest");
+
+    // The old pair is gone and the new one keeps the game and the roles.
+    game_mock.query_games("host", "guest").unwrap_err();
+    let info = game_mock.query_games("wallet", "guest").unwrap().info;
+    assert_eq!(info.host_role, host_role);
+    assert_eq!(info.current_game.unwrap().board()[0], Player::X);
+
+    game_mock.play_in("guest", "wallet", "guest", 4).unwrap();
+    game_mock.play_in("wallet", "wallet", "guest", 8).unwrap();
+    let err = game_mock.play_in("host", "wallet", "guest", 2).unwrap_err();
+    assert_eq!(
+        ContractError::NotInvolved {
+            host: "wallet".to_string(),
+            guest: "guest".to_string(),
+            player: "host".to_string()
+        },
+        err.downcast().unwrap()
+    );
 }
 
 #[test]
-fn invalid_invitation_game_in_progress() {
+fn transfer_seat_to_opponent() {
     let mut game_mock = GameMock::new();
     game_mock.init_game("host", "guest");
 
-    let err = game_mock.invite("host", "guest").unwrap_err();
+    let err = game_mock
+        .transfer_seat("guest", "host", "guest", "host")
+        .unwrap_err();
     assert_eq!(
-        ContractError::GameInProgress {
-            host: "host".to_string(),
-            guest: "guest".to_string()
+        ContractError::SelfPlay {
+            player: "host".to_string()
         },
         err.downcast().unwrap()
     );
 }
 
 #[test]
-fn accept_invitation() {
+fn force_end_game() {
     let mut game_mock = GameMock::new();
-    game_mock.invite("host", "guest").unwrap();
+    game_mock.init_game("host", "guest");
+    game_mock.play("host", 0).unwrap();
 
-    let resp = game_mock.accept("host", "guest").unwrap();
+    let err = game_mock
+        .force_end_game("host", "host", "guest")
+        .unwrap_err();
+    assert_eq!(ContractError::Unauthorized, err.downcast().unwrap());
+
+    let resp = game_mock.force_end_game("Owner", "host", "guest").unwrap();
     let event = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
-    assert_eq!(attribute!(event, "action"), "accept invitation");
+    assert_eq!(attribute!(event, "action"), "force_end");
     assert_eq!(attribute!(event, "host"), "host");
     assert_eq!(attribute!(event, "guest"), "guest");
 
-    let resp = game_mock.query_games("host", "guest").unwrap();
-    assert_eq!(resp.info.host, "host");
-    assert_eq!(resp.info.guest, "guest");
-    assert_eq!(resp.info.pending_invitation, false);
-    assert_eq!(resp.info.current_game.unwrap().board(), &[Player::None; 9]);
-    assert_eq!(resp.info.current_game.unwrap().turn(), Player::X);
+    let info = game_mock.query_games("host", "guest").unwrap().info;
+    assert_eq!(info.current_game, None);
+    assert_eq!(info.completed_games.len(), 1);
+    assert_eq!(
+        info.completed_games[0].result(),
+        GameResult::Abandoned { by: None }
+    );
+    assert_eq!(game_mock.query_total_games().unwrap().total_completed, 1);
+
+    // A new game can be started afterwards.
+    game_mock.init_game("host", "guest");
 }
 
 #[test]
-fn no_pending_invitation() {
-    let mut game_mock = GameMock::new();
-    game_mock.invite("host", "guest").unwrap();
-    game_mock.reject("host", "guest").unwrap();
+fn force_end_match() {
+    let mut game_mock = GameMock::instantiate_with(&InstantiateMsg {
+        admin: None,
+        invite_timeout: None,
+        move_timeout: None,
+        store_history: None,
+        max_active_per_player: Some(1),
+        max_illegal_moves: None,
+        invite_cooldown: None,
+    });
+    game_mock.invite_match("host", "guest", 3).unwrap();
+    game_mock.accept("host", "guest").unwrap();
+    game_mock.play("host", 0).unwrap();
 
-    let err = game_mock.accept("host", "guest").unwrap_err();
+    // The whole match ends rather than only its current game.
+    game_mock.force_end_game("Owner", "host", "guest").unwrap();
+    let info = game_mock.query_games("host", "guest").unwrap().info;
+    assert_eq!(info.current_game, None);
+    assert_eq!(info.status, GameStatus::Completed);
     assert_eq!(
-        ContractError::NoPendingInvitation {
-            host: "host".to_string(),
-            guest: "guest".to_string()
-        },
-        err.downcast().unwrap()
+        info.completed_games[0].result(),
+        GameResult::Abandoned { by: None }
+    );
+    assert_eq!(
+        game_mock
+            .query_match_status("host", "guest")
+            .unwrap()
+            .match_winner,
+        None
     );
+
+    // Both players are free to start other games.
+    game_mock.init_game("host", "other");
+    game_mock.init_game("guest", "player");
 }
 
 #[test]
-fn reject_invitation() {
+fn import_game() {
+    use Player::{None as E, O, X};
     let mut game_mock = GameMock::new();
-    game_mock.invite("host", "guest").unwrap();
+    let completed = Game::from_board([X, X, X, O, O, E, E, E, E]).unwrap();
 
-    let resp = game_mock.reject("host", "guest").unwrap();
+    let err = game_mock
+        .import_game("host", "host", "guest", None, vec![completed], false)
+        .unwrap_err();
+    assert_eq!(ContractError::Unauthorized, err.downcast().unwrap());
+
+    // A game in progress can't be imported as completed.
+    let err = game_mock
+        .import_game("Owner", "host", "guest", None, vec![Game::new()], false)
+        .unwrap_err();
+    assert_eq!(
+        ContractError::InvalidImport {
+            reason: "a completed game is not over".to_string()
+        },
+        err.downcast().unwrap()
+    );
+
+    let resp = game_mock
+        .import_game("Owner", "host", "guest", None, vec![completed], false)
+        .unwrap();
     let event = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
-    assert_eq!(attribute!(event, "action"), "reject invitation");
-    assert_eq!(attribute!(event, "host"), "host");
-    assert_eq!(attribute!(event, "guest"), "guest");
+    assert_eq!(attribute!(event, "action"), "import_game");
 
-    let resp = game_mock.query_games("host", "guest").unwrap();
-    assert_eq!(resp.info.host, "host");
-    assert_eq!(resp.info.guest, "guest");
-    assert_eq!(resp.info.pending_invitation, false);
-    assert_eq!(resp.info.current_game, None);
-}
+    let info = game_mock.query_games("host", "guest").unwrap().info;
+    assert_eq!(info.status, GameStatus::Completed);
+    assert_eq!(info.completed_games, vec![completed]);
+    assert_eq!(
+        info.completed_results[0].winner_addr,
+        Some("host".to_string())
+    );
+    assert_eq!(game_mock.query_games_count().unwrap().count, 1);
 
-#[test]
-fn invalid_reject() {
-    let mut game_mock = GameMock::new();
+    // The imported pair can keep playing.
     game_mock.init_game("host", "guest");
-
-    let err = game_mock.reject("host", "guest").unwrap_err();
+    let err = game_mock
+        .import_game("Owner", "host", "guest", None, vec![], false)
+        .unwrap_err();
     assert_eq!(
-        ContractError::NoPendingInvitation {
+        ContractError::GameInProgress {
             host: "host".to_string(),
             guest: "guest".to_string()
         },
         err.downcast().unwrap()
     );
+
+    // A forced import keeps the nonces and the game count of the pair.
+    game_mock
+        .play_with_nonce("host", "host", "guest", 0, 5)
+        .unwrap();
+    let game_ref = game_mock
+        .query_games("host", "guest")
+        .unwrap()
+        .info
+        .game_ref;
+    game_mock
+        .import_game("Owner", "host", "guest", None, vec![], true)
+        .unwrap();
+    let info = game_mock.query_games("host", "guest").unwrap().info;
+    assert_eq!(info.completed_games, vec![]);
+    assert_eq!(info.game_ref, game_ref);
+
+    game_mock.init_game("host", "guest");
+    let err = game_mock
+        .play_with_nonce("host", "host", "guest", 0, 5)
+        .unwrap_err();
+    assert_eq!(
+        ContractError::DuplicateNonce { nonce: 5 },
+        err.downcast().unwrap()
+    );
 }
 
 #[test]
-fn valid_play() {
+fn time_bank() {
     let mut game_mock = GameMock::new();
-    game_mock.init_game("host", "guest");
+    let err = game_mock
+        .invite_with_time_bank("host", "guest", 0)
+        .unwrap_err();
+    assert_eq!(ContractError::InvalidTimeBank, err.downcast().unwrap());
 
-    // play
-    let resp = game_mock.play("host", 4).unwrap();
+    game_mock
+        .invite_with_time_bank("host", "guest", 100)
+        .unwrap();
+    game_mock.accept("host", "guest").unwrap();
 
-    let event = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
-    assert_eq!(attribute!(event, "action"), "play");
-    assert_eq!(attribute!(event, "host"), "host");
-    assert_eq!(attribute!(event, "guest"), "guest");
-    assert_eq!(attribute!(event, "cell"), "4");
+    // The host spends 90 seconds of their bank, the guest plays right away.
+    for (player, cell, seconds) in [
+        ("host", 0, 30),
+        ("guest", 4, 0),
+        ("host", 1, 60),
+        ("guest", 2, 0),
+    ] {
+        game_mock.advance_time(seconds);
+        game_mock.play(player, cell).unwrap();
+    }
 
-    let resp: GamesResponse = game_mock.query_games("host", "guest").unwrap();
+    // The host has 10 seconds left, well within the move timeout.
+    game_mock.advance_time(5);
+    let err = game_mock
+        .claim_timeout("guest", "host", "guest")
+        .unwrap_err();
     assert_eq!(
-        GamesInfo {
-            host: "host".to_string(),
-            guest: "guest".to_string(),
-            host_role: Player::X,
-            guest_role: Player::O,
-            current_game: Some(Game {
-                board: [
-                    Player::None,
-                    Player::None,
-                    Player::None,
-                    Player::None,
-                    Player::X,
-                    Player::None,
-                    Player::None,
-                    Player::None,
-                    Player::None
-                ],
-                turn: Player::O,
-            }),
-            pending_invitation: false,
-            completed_games: vec![]
+        ContractError::MoveNotTimedOut {
+            deadline: game_mock.block_time().plus_seconds(5)
         },
-        resp.info
+        err.downcast().unwrap()
+    );
+
+    game_mock.advance_time(5);
+    let err = game_mock.play("host", 6).unwrap_err();
+    assert_eq!(ContractError::OutOfTime, err.downcast().unwrap());
+    // A sequence of moves draws from the same bank.
+    let err = game_mock
+        .play_sequence("host", "host", "guest", vec![6])
+        .unwrap_err();
+    assert_eq!(ContractError::OutOfTime, err.downcast().unwrap());
+    game_mock.claim_timeout("guest", "host", "guest").unwrap();
+
+    let info = game_mock.query_games("host", "guest").unwrap().info;
+    assert_eq!(
+        info.completed_results[0].result,
+        GameResult::Abandoned {
+            by: Some(Player::X)
+        }
     );
+    assert_eq!(game_mock.query_stats("host").unwrap().timeouts, 1);
 }
 
 #[test]
-fn not_your_turn() {
+fn win_streaks() {
     let mut game_mock = GameMock::new();
-    game_mock.init_game("host", "guest");
+    // The host plays X and wins on the top row, or loses on the middle row.
+    let mut play_game = |host_wins: bool| {
+        game_mock.init_game("host", "guest");
+        let moves = if host_wins {
+            [
+                ("host", 0),
+                ("guest", 3),
+                ("host", 1),
+                ("guest", 4),
+                ("host", 2),
+            ]
+        } else {
+            [
+                ("host", 0),
+                ("guest", 3),
+                ("host", 1),
+                ("guest", 4),
+                ("host", 8),
+            ]
+        };
+        for (player, cell) in moves {
+            game_mock.play(player, cell).unwrap();
+        }
+        if !host_wins {
+            game_mock.play("guest", 5).unwrap();
+        }
+    };
+    for host_wins in [true, true, false, true] {
+        play_game(host_wins);
+    }
 
-    // play
-    game_mock.play("host", 4).unwrap();
+    let stats = game_mock.query_stats("host").unwrap();
+    assert_eq!(stats.longest_streak, 2);
+    assert_eq!(stats.current_streak, 1);
+    let stats = game_mock.query_stats("guest").unwrap();
+    assert_eq!(stats.longest_streak, 1);
+    assert_eq!(stats.current_streak, 0);
+}
 
-    let err = game_mock.play("host", 5).unwrap_err();
+#[test]
+fn clear_my_history() {
+    let mut game_mock = GameMock::new();
+    game_mock.init_game("host", "guest");
+    game_mock.resign("guest", "host", "guest").unwrap();
+    let err = game_mock
+        .clear_my_history("player", "host", "guest")
+        .unwrap_err();
     assert_eq!(
-        ContractError::GameError((GameError::NotYourTurn).into()),
+        ContractError::NotInvolved {
+            host: "host".to_string(),
+            guest: "guest".to_string(),
+            player: "player".to_string()
+        },
         err.downcast().unwrap()
     );
+
+    // A request alone, even sent twice, doesn't delete anything.
+    for _ in 0..2 {
+        let resp = game_mock
+            .clear_my_history("guest", "host", "guest")
+            .unwrap();
+        let event = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+        assert_eq!(attribute!(event, "action"), "clear_history");
+        assert_eq!(attribute!(event, "cleared"), "false");
+    }
+    let info = game_mock.query_games("host", "guest").unwrap().info;
+    assert_eq!(info.completed_games.len(), 1);
+
+    // The opponent confirms.
+    let resp = game_mock.clear_my_history("host", "host", "guest").unwrap();
+    let event = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+    assert_eq!(attribute!(event, "cleared"), "true");
+    let info = game_mock.query_games("host", "guest").unwrap().info;
+    assert!(info.completed_games.is_empty());
+    assert_eq!(game_mock.query_global_stats().unwrap().total_completed, 1);
+    // The next game doesn't reuse the reference of the cleared one.
+    assert_eq!(
+        info.game_ref,
+        game_ref(&Addr::unchecked("host"), &Addr::unchecked("guest"), 1)
+    );
+
+    // When the players have a pair in each order, each history is cleared on its own.
+    game_mock.init_game("guest", "host");
+    game_mock.resign("host", "guest", "host").unwrap();
+    game_mock.init_game("host", "guest");
+    game_mock.resign("guest", "host", "guest").unwrap();
+    game_mock.clear_my_history("host", "guest", "host").unwrap();
+    let resp = game_mock
+        .clear_my_history("guest", "guest", "host")
+        .unwrap();
+    let event = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+    assert_eq!(attribute!(event, "cleared"), "true");
+    let info = game_mock.query_games("guest", "host").unwrap().info;
+    assert!(info.completed_games.is_empty());
+    let info = game_mock.query_games("host", "guest").unwrap().info;
+    assert_eq!(info.completed_games.len(), 1);
 }
 
 #[test]
-fn invalid_cell() {
+fn claim_result() {
+    use Player::{None as E, O, X};
     let mut game_mock = GameMock::new();
-    game_mock.init_game("host", "guest");
+    game_mock.init_game("host", "other");
 
-    // play
-    let err = game_mock.play("host", 10).unwrap_err();
+    let err = game_mock.claim_result("host", "host", "other").unwrap_err();
     assert_eq!(
-        ContractError::GameError((GameError::InvalidMove(10)).into()),
+        ContractError::GameNotOver {
+            host: "host".to_string(),
+            guest: "other".to_string()
+        },
         err.downcast().unwrap()
     );
+
+    // A game imported once over stays in progress until its result is claimed.
+    let over = Game::from_board([X, X, X, O, O, E, E, E, E]).unwrap();
+    game_mock
+        .import_game("Owner", "host", "guest", Some(over), vec![], false)
+        .unwrap();
+    assert_eq!(
+        game_mock.query_games("host", "guest").unwrap().info.status,
+        GameStatus::Active
+    );
+
+    let err = game_mock
+        .claim_result("player", "host", "guest")
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast().unwrap(),
+        ContractError::NotInvolved { .. }
+    ));
+
+    let resp = game_mock.claim_result("guest", "host", "guest").unwrap();
+    let event = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+    assert_eq!(attribute!(event, "action"), "claim_result");
+    assert_eq!(attribute!(event, "winner"), "host");
+
+    let info = game_mock.query_games("host", "guest").unwrap().info;
+    assert_eq!(info.status, GameStatus::Completed);
+    assert_eq!(info.current_game, None);
+    assert_eq!(info.completed_games.len(), 1);
+    assert_eq!(game_mock.query_global_stats().unwrap().total_completed, 1);
 }
 
 #[test]
-fn cell_already_taken() {
+fn purge_pair() {
     let mut game_mock = GameMock::new();
     game_mock.init_game("host", "guest");
+    game_mock.play("host", 0).unwrap();
 
-    game_mock.play("host", 4).unwrap();
-    let err = game_mock.play("guest", 4).unwrap_err();
+    let err = game_mock
+        .purge_pair("host", "host", "guest", false)
+        .unwrap_err();
+    assert_eq!(ContractError::Unauthorized, err.downcast().unwrap());
 
+    // The game is still in progress.
+    let err = game_mock
+        .purge_pair("Owner", "host", "guest", false)
+        .unwrap_err();
     assert_eq!(
-        ContractError::GameError((GameError::InvalidMove(4)).into()),
+        ContractError::GameInProgress {
+            host: "host".to_string(),
+            guest: "guest".to_string()
+        },
         err.downcast().unwrap()
     );
+
+    game_mock.resign("guest", "host", "guest").unwrap();
+    let resp = game_mock
+        .purge_pair("Owner", "host", "guest", false)
+        .unwrap();
+    let event = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+    assert_eq!(attribute!(event, "action"), "purge_pair");
+    game_mock.query_games("host", "guest").unwrap_err();
+    assert_eq!(game_mock.query_games_count().unwrap().count, 0);
+
+    // A game in progress can be purged when forced.
+    game_mock.init_game("host", "guest");
+    game_mock
+        .purge_pair("Owner", "host", "guest", true)
+        .unwrap();
+    game_mock.query_games("host", "guest").unwrap_err();
 }
 
 #[test]
-fn game_not_found() {
+fn concede_match() {
     let mut game_mock = GameMock::new();
-    let err = game_mock.play("host", 0).unwrap_err();
-
+    game_mock.init_game("host", "guest");
+    let err = game_mock
+        .concede_match("host", "host", "guest")
+        .unwrap_err();
     assert_eq!(
-        ContractError::StdError(
-            (StdError::NotFound {
-                kind: "tic_tac_toe::state::Games".to_string()
-            })
-            .into()
-        ),
+        ContractError::NotInMatch {
+            host: "host".to_string(),
+            guest: "guest".to_string()
+        },
         err.downcast().unwrap()
     );
+
+    let mut game_mock = GameMock::new();
+    game_mock.invite_match("host", "guest", 2).unwrap();
+    game_mock.accept("host", "guest").unwrap();
+
+    // The host wins the first game.
+    for (player, cell) in [
+        ("host", 0),
+        ("guest", 1),
+        ("host", 3),
+        ("guest", 5),
+        ("host", 6),
+    ] {
+        game_mock.play(player, cell).unwrap();
+    }
+
+    let resp = game_mock.concede_match("host", "host", "guest").unwrap();
+    let event = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+    assert_eq!(attribute!(event, "action"), "match_conceded");
+    assert_eq!(attribute!(event, "winner"), "guest");
+
+    let resp = game_mock.query_match_status("host", "guest").unwrap();
+    assert_eq!(resp.host_score, 1);
+    assert_eq!(resp.guest_score, 2);
+    assert!(resp.match_over);
+    assert_eq!(resp.match_winner, Some("guest".to_string()));
+
+    let info = game_mock.query_games("host", "guest").unwrap().info;
+    assert_eq!(info.status, GameStatus::Completed);
+    assert_eq!(info.completed_games.len(), 2);
 }
 
 #[test]
-fn player_not_in_game() {
+fn resign() {
     let mut game_mock = GameMock::new();
     game_mock.init_game("host", "guest");
+    game_mock.play("host", 0).unwrap();
 
-    let err = game_mock.play("player", 0).unwrap_err();
+    let err = game_mock.resign("player", "host", "guest").unwrap_err();
     assert_eq!(
         ContractError::NotInvolved {
-            player: "player".to_string(),
             host: "host".to_string(),
-            guest: "guest".to_string()
+            guest: "guest".to_string(),
+            player: "player".to_string()
         },
         err.downcast().unwrap()
     );
+
+    let resp = game_mock.resign("guest", "host", "guest").unwrap();
+    let event = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+    assert_eq!(attribute!(event, "action"), "resign");
+    assert_eq!(attribute!(event, "player"), "guest");
+    let game_over = resp
+        .events
+        .iter()
+        .find(|ev| ev.ty == "wasm-game_over")
+        .unwrap();
+    assert_eq!(attribute!(game_over, "winner_address"), "host");
+
+    // Nobody completed a line, the resigned game is still won by the host.
+    let resp = game_mock.query_last_game("host", "guest").unwrap();
+    assert_eq!(
+        resp.result,
+        Some(GameResult::Abandoned {
+            by: Some(Player::O)
+        })
+    );
+    assert_eq!(resp.game.unwrap().winner(), None);
+    assert_eq!(
+        game_mock.query_winner("host", "guest").unwrap(),
+        Some("host".to_string())
+    );
+    let info = game_mock.query_games("host", "guest").unwrap().info;
+    assert_eq!(
+        info.completed_results[0].winner_addr,
+        Some("host".to_string())
+    );
 }
 
 #[test]
-fn game_over_winner_x() {
+fn claim_timeout() {
     let mut game_mock = GameMock::new();
     game_mock.init_game("host", "guest");
-
-    // play
     game_mock.play("host", 0).unwrap();
-    game_mock.play("guest", 1).unwrap();
-    game_mock.play("host", 3).unwrap();
-    game_mock.play("guest", 5).unwrap();
-    game_mock.play("host", 6).unwrap();
 
-    let resp = game_mock.query_games("host", "guest").unwrap();
+    // The guest still has time to play.
+    game_mock.advance_time(DEFAULT_MOVE_TIMEOUT - 1);
+    let err = game_mock
+        .claim_timeout("host", "host", "guest")
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast().unwrap(),
+        ContractError::MoveNotTimedOut { .. }
+    ));
 
-    assert!(resp.info.completed_games[0].is_over());
-    assert_eq!(Player::X, resp.info.completed_games[0].winner().unwrap());
+    game_mock.advance_time(1);
+
+    // The player to move can't claim the timeout.
+    let err = game_mock
+        .claim_timeout("guest", "host", "guest")
+        .unwrap_err();
+    assert_eq!(ContractError::ClaimOnOwnTurn, err.downcast().unwrap());
+
+    let resp = game_mock.claim_timeout("host", "host", "guest").unwrap();
+    let event = resp.events.iter().find(|ev| ev.ty == "wasm").unwrap();
+    assert_eq!(attribute!(event, "action"), "claim_timeout");
+    assert_eq!(attribute!(event, "claimant"), "host");
+
+    // The guest lost the game by running out of time.
+    let resp = game_mock.query_last_game("host", "guest").unwrap();
     assert_eq!(
-        GamesResponse {
-            info: GamesInfo {
-                host: "host".to_string(),
-                guest: "guest".to_string(),
-                host_role: Player::X,
-                guest_role: Player::O,
-                pending_invitation: false,
-                current_game: None,
-                completed_games: vec![Game {
-                    board: [
-                        Player::X,
-                        Player::O,
-                        Player::None,
-                        Player::X,
-                        Player::None,
-                        Player::O,
-                        Player::X,
-                        Player::None,
-                        Player::None,
-                    ],
-                    turn: Player::O,
-                }]
-            },
+        resp.result,
+        Some(GameResult::Abandoned {
+            by: Some(Player::O)
+        })
+    );
+
+    // The timeout is recorded against the guest only, the host won the game.
+    assert_eq!(game_mock.query_stats("guest").unwrap().timeouts, 1);
+    assert_eq!(
+        game_mock.query_stats("host").unwrap(),
+        PlayerStats {
+            timeouts: 0,
+            current_streak: 1,
+            longest_streak: 1
+        }
+    );
+
+    // The timeout can't be claimed twice.
+    let err = game_mock
+        .claim_timeout("host", "host", "guest")
+        .unwrap_err();
+    assert_eq!(
+        ContractError::GameFinished {
+            host: "host".to_string(),
+            guest: "guest".to_string(),
+            completed: 1
         },
-        resp
+        err.downcast().unwrap()
     );
 }
 
 #[test]
-fn game_over_with_draw() {
+fn is_my_turn() {
     let mut game_mock = GameMock::new();
+
+    // No game at all.
+    assert!(!game_mock.query_is_my_turn("host", "guest", "host").unwrap());
+
     game_mock.init_game("host", "guest");
+    assert!(game_mock.query_is_my_turn("host", "guest", "host").unwrap());
+    assert!(!game_mock
+        .query_is_my_turn("host", "guest", "guest")
+        .unwrap());
 
-    // play
     game_mock.play("host", 0).unwrap();
-    game_mock.play("guest", 4).unwrap();
-    game_mock.play("host", 8).unwrap();
+    assert!(!game_mock.query_is_my_turn("host", "guest", "host").unwrap());
+    assert!(game_mock
+        .query_is_my_turn("host", "guest", "guest")
+        .unwrap());
+
+    // A player who is not involved never has the turn.
+    assert!(!game_mock
+        .query_is_my_turn("host", "guest", "player")
+        .unwrap());
+}
+
+#[test]
+fn dashboard() {
+    let mut game_mock = GameMock::new();
+    // A completed game, then an active one against the same opponent.
+    game_mock.init_game("host", "guest");
+    game_mock.play("host", 0).unwrap();
     game_mock.play("guest", 3).unwrap();
-    game_mock.play("host", 5).unwrap();
-    game_mock.play("guest", 2).unwrap();
-    game_mock.play("host", 6).unwrap();
-    game_mock.play("guest", 7).unwrap();
     game_mock.play("host", 1).unwrap();
+    game_mock.play("guest", 4).unwrap();
+    game_mock.play("host", 2).unwrap();
+    game_mock.init_game("host", "guest");
+    // An incoming and an outgoing invitation.
+    game_mock.invite("friend", "host").unwrap();
+    game_mock.invite("host", "other").unwrap();
+    // A game the player is not involved in.
+    game_mock.init_game("friend", "other");
 
-    let resp = game_mock.query_games("host", "guest").unwrap();
-    assert!(resp.info.completed_games[0].is_over());
-    assert!(resp.info.completed_games[0].winner().is_none());
+    let resp = game_mock.query_dashboard("host").unwrap();
+    assert_eq!(resp.active.len(), 1);
+    assert_eq!(resp.active[0].host, "host");
+    assert_eq!(resp.active[0].guest, "guest");
+    assert_eq!(resp.pending_incoming, vec!["friend".to_string()]);
+    assert_eq!(resp.pending_outgoing, vec!["other".to_string()]);
     assert_eq!(
-        GamesResponse {
-            info: GamesInfo {
-                host: "host".to_string(),
-                guest: "guest".to_string(),
-                host_role: Player::X,
-                guest_role: Player::O,
-                pending_invitation: false,
-                current_game: None,
-                completed_games: vec![Game {
-                    board: [
-                        Player::X,
-                        Player::X,
-                        Player::O,
-                        Player::O,
-                        Player::O,
-                        Player::X,
-                        Player::X,
-                        Player::O,
-                        Player::X,
-                    ],
-                    turn: Player::O,
-                }]
-            },
-        },
-        resp
+        resp.recent_results,
+        vec![GameResult::Winner {
+            player: Player::X,
+            line: [0, 1, 2]
+        }]
     );
 }
@@ -1,5 +1,5 @@
-use crate::game::GameError;
-use cosmwasm_std::StdError;
+use crate::game::{GameError, Player};
+use cosmwasm_std::{StdError, Timestamp};
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -10,14 +10,82 @@ pub enum ContractError {
     GameError(#[from] GameError),
     #[error("A Game in progress already exists between {host} and {guest}")]
     GameInProgress { host: String, guest: String },
+    #[error("An invitation from {host} to {guest} is already pending")]
+    InvitationAlreadyPending { host: String, guest: String },
+    #[error("The invitation from {host} to {guest} is pending with other terms")]
+    TermsMismatch { host: String, guest: String },
     #[error("No pending invitation for {guest} from {host}")]
     NoPendingInvitation { host: String, guest: String },
+    #[error("{player} was not invited by {host}")]
+    NotInvited { host: String, player: String },
+    #[error("The invitation from {host} to {guest} has not expired yet")]
+    InvitationNotExpired { host: String, guest: String },
+    #[error("The reason must be at most {max} characters long")]
+    ReasonTooLong { max: usize },
+    #[error("The invitation message must be at most {max} characters long")]
+    MessageTooLong { max: usize },
+    #[error("The match target must be greater than zero")]
+    InvalidMatchTarget,
+    #[error("The time bank must be greater than zero")]
+    InvalidTimeBank,
+    #[error("The player ran out of time")]
+    OutOfTime,
+    #[error("The nickname must be between 1 and {max} characters long")]
+    InvalidNickname { max: usize },
+    #[error("The message must be between 1 and {max} characters long")]
+    InvalidMessage { max: usize },
+    #[error("The host already chose to play {symbol:?}")]
+    SymbolConflict { symbol: Player },
+    #[error("The preset is not a legal position")]
+    IllegalPreset,
     #[error("No game in progress between {host} and {guest}")]
     NoGameInProgress { host: String, guest: String },
+    #[error("The game between {host} and {guest} is not over")]
+    GameNotOver { host: String, guest: String },
+    #[error(
+        "No game in progress between {host} and {guest}, {completed} game(s) already completed"
+    )]
+    GameFinished {
+        host: String,
+        guest: String,
+        completed: usize,
+    },
+    #[error("No completed game at index {index}, only {completed} game(s) completed")]
+    GameIndexOutOfRange { index: usize, completed: usize },
     #[error("The player {player} is not involved in a game between {host} and {guest}")]
     NotInvolved {
         host: String,
         guest: String,
         player: String,
     },
+    #[error("The player {player} can't play against themselves")]
+    SelfPlay { player: String },
+    #[error("Games between {host} and {guest} already exist")]
+    PairAlreadyExists { host: String, guest: String },
+    #[error("This message does not accept funds")]
+    UnexpectedFunds,
+    #[error("The games between {host} and {guest} are not a match")]
+    NotInMatch { host: String, guest: String },
+    #[error("The nonce {nonce} was already used")]
+    DuplicateNonce { nonce: u64 },
+    #[error("Invalid import: {reason}")]
+    InvalidImport { reason: String },
+    #[error("The guest can be invited again in {remaining} second(s)")]
+    InviteCooldown { remaining: u64 },
+    #[error("Unauthorized")]
+    Unauthorized,
+    #[error("{player} already has {limit} game(s) in progress")]
+    TooManyActiveGames { player: String, limit: u32 },
+    #[error("At most {max} pairs of players can be queried at once")]
+    BatchTooLarge { max: usize },
+    #[error("A sequence must contain at least one move")]
+    EmptySequence,
+    #[error("Too many {resource}")]
+    CapacityExceeded { resource: String },
+    #[error("The player to move has until {deadline} to play")]
+    MoveNotTimedOut { deadline: Timestamp },
+    #[error("A timeout can't be claimed on your own turn")]
+    ClaimOnOwnTurn,
+    #[error("Invalid config: {reason}")]
+    InvalidConfig { reason: String },
 }
@@ -1,26 +1,181 @@
 use cosmwasm_schema::cw_serde;
+use cosmwasm_schema::schemars::{gen::SchemaGenerator, schema::Schema, JsonSchema};
+use cosmwasm_std::Timestamp;
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
 use thiserror::Error;
 
 /// A player in the game.
+///
+/// On the wire `X` and `O` are encoded as `"x"` and `"o"`, and `None` (an empty cell) as `null`. The variant names
+/// `"X"`, `"O"` and `"None"` stored by earlier versions are still accepted when deserializing.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(from = "StoredPlayer", into = "Option<Symbol>")]
+pub enum Player {
+    X,
+    O,
+    None,
+}
+
+impl Player {
+    /// Returns the opponent of the player. `None` has no opponent.
+    pub fn opponent(self) -> Player {
+        match self {
+            Player::X => Player::O,
+            Player::O => Player::X,
+            Player::None => Player::None,
+        }
+    }
+
+    /// Returns the symbol of the player. An empty cell is represented by a space.
+    pub fn as_symbol(self) -> char {
+        match self {
+            Player::X => 'X',
+            Player::O => 'O',
+            Player::None => ' ',
+        }
+    }
+}
+
+impl fmt::Display for Player {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_symbol())
+    }
+}
+
+impl FromStr for Player {
+    type Err = GameError;
+
+    /// Parses `X` or `O`, in any case. An empty cell can't be parsed.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "X" | "x" => Ok(Player::X),
+            "O" | "o" => Ok(Player::O),
+            _ => Err(GameError::InvalidPlayer),
+        }
+    }
+}
+
+/// The JSON encoding of an occupied cell.
 #[cw_serde]
 #[derive(Copy)]
-pub enum Player {
+pub enum Symbol {
+    X,
+    O,
+}
+
+impl From<Option<Symbol>> for Player {
+    fn from(symbol: Option<Symbol>) -> Self {
+        match symbol {
+            Some(Symbol::X) => Player::X,
+            Some(Symbol::O) => Player::O,
+            None => Player::None,
+        }
+    }
+}
+
+/// The encodings of a player accepted when deserializing, the current one first.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredPlayer {
+    Symbol(Option<Symbol>),
+    Legacy(LegacyPlayer),
+}
+
+/// The encoding of a player by its variant name, used before the symbols.
+#[derive(Deserialize)]
+enum LegacyPlayer {
     X,
     O,
     None,
 }
 
+impl From<StoredPlayer> for Player {
+    fn from(stored: StoredPlayer) -> Self {
+        match stored {
+            StoredPlayer::Symbol(symbol) => symbol.into(),
+            StoredPlayer::Legacy(LegacyPlayer::X) => Player::X,
+            StoredPlayer::Legacy(LegacyPlayer::O) => Player::O,
+            StoredPlayer::Legacy(LegacyPlayer::None) => Player::None,
+        }
+    }
+}
+
+impl JsonSchema for Player {
+    fn schema_name() -> String {
+        "Player".to_string()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        <Option<Symbol>>::json_schema(gen)
+    }
+}
+
+impl From<Player> for Option<Symbol> {
+    fn from(player: Player) -> Self {
+        match player {
+            Player::X => Some(Symbol::X),
+            Player::O => Some(Symbol::O),
+            Player::None => None,
+        }
+    }
+}
+
+/// The result of a game.
+#[cw_serde]
+#[derive(Copy)]
+pub enum GameResult {
+    /// The game was won by the given player, who completed the given line.
+    Winner { player: Player, line: [usize; 3] },
+    /// The board is full and nobody won.
+    Draw,
+    /// The game was ended before being over, by the given player if any.
+    Abandoned { by: Option<Player> },
+    /// The game is not over yet.
+    InProgress,
+}
+
+impl GameResult {
+    /// Returns the player who won the game: the one who completed a line, or the opponent of the player who abandoned
+    /// the game. Returns `None` for a draw, a game ended by the admin or a game in progress.
+    pub fn winner(&self) -> Option<Player> {
+        match *self {
+            GameResult::Winner { player, .. } => Some(player),
+            GameResult::Abandoned { by: Some(player) } => Some(player.opponent()),
+            _ => None,
+        }
+    }
+}
+
 /// An error that can occur when playing a game.
 #[derive(Error, Debug, PartialEq)]
 pub enum GameError {
     /// The player tried to play out of turn.
     #[error("Not your turn")]
     NotYourTurn,
+    /// The player is not one of the two players of the game.
+    #[error("Invalid player")]
+    InvalidPlayer,
     /// The player tried to play on an occupied or an invalid cell.
     #[error("Cell {0} is already occupied")]
     InvalidMove(usize),
+    /// The player tried to play outside of the board.
+    #[error("Cell ({row}, {col}) is out of the board")]
+    OutOfBounds { row: usize, col: usize },
+    /// The player tried to play a cell index outside of the board.
+    #[error("Cell {0} is out of the board")]
+    CellOutOfBounds(usize),
+    /// The board can't be reached by playing a game.
+    #[error("The board is not reachable")]
+    IllegalBoard,
+    /// The player tried to play once the game is over.
+    #[error("The game is over")]
+    GameOver,
 }
 
+/// The number of rows and columns of the board.
+pub const BOARD_SIZE: usize = 3;
+
 /// The winning combinations of tic-tac-toe.
 const WINNING_COMBINATIONS: [[usize; 3]; 8] = [
     [0, 1, 2],
@@ -33,12 +188,38 @@ const WINNING_COMBINATIONS: [[usize; 3]; 8] = [
     [2, 4, 6],
 ];
 
+/// An upper bound of the minimax scores.
+const MAX_SCORE: i32 = 100;
+
 /// A tic-tac-toe game.
 #[cw_serde]
 #[derive(Copy)]
 pub struct Game {
     pub(crate) board: [Player; 9],
     pub(crate) turn: Player,
+    /// The time at which the game started.
+    pub(crate) created_at: Option<Timestamp>,
+    /// The time at which the game ended.
+    pub(crate) completed_at: Option<Timestamp>,
+    /// The time at which the last move was played.
+    #[serde(default)]
+    pub(crate) last_move_at: Option<Timestamp>,
+    /// Whether the game was ended before being over.
+    #[serde(default)]
+    pub(crate) abandoned: bool,
+    /// The player who abandoned the game, if any.
+    #[serde(default)]
+    pub(crate) abandoned_by: Option<Player>,
+    /// The symbol played by the host, recorded when the game completes as the host may play the other symbol in the
+    /// next games. Not recorded for games completed by earlier versions.
+    #[serde(default)]
+    pub(crate) host_role: Option<Player>,
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Game {
@@ -47,11 +228,160 @@ impl Game {
         Game {
             board: [Player::None; 9],
             turn: Player::X,
+            created_at: None,
+            completed_at: None,
+            last_move_at: None,
+            abandoned: false,
+            abandoned_by: None,
+            host_role: None,
+        }
+    }
+
+    /// Creates a new game from a preset of moves. The player whose turn it is depends on the number of moves.
+    ///
+    /// Returns `None` if the preset is illegal: a cell is out of the board or played twice, a move is made by
+    /// `None`, `X` did not play exactly as many moves as `O` or one more, or the game is already over.
+    pub fn from_preset(preset: &[(usize, Player)]) -> Option<Game> {
+        let mut board = [Player::None; 9];
+        for &(cell, player) in preset {
+            match board.get_mut(cell) {
+                Some(cell) if *cell == Player::None && player != Player::None => *cell = player,
+                _ => return None,
+            }
+        }
+
+        let game = Game::from_board(board).ok()?;
+        if game.is_over() {
+            return None;
+        }
+        Some(game)
+    }
+
+    /// Creates a game from a board. The player whose turn it is depends on the number of moves.
+    ///
+    /// Returns `GameError::IllegalBoard` if the board can't be reached by playing: `X` did not play exactly as many
+    /// moves as `O` or one more, both players completed a line, or the winner was not the last one to play.
+    pub fn from_board(board: [Player; 9]) -> Result<Game, GameError> {
+        let count = |player| board.iter().filter(|&&p| p == player).count();
+        let (x_count, o_count) = (count(Player::X), count(Player::O));
+        if x_count != o_count && x_count != o_count + 1 {
+            return Err(GameError::IllegalBoard);
+        }
+
+        let wins = |player: Player| {
+            WINNING_COMBINATIONS
+                .iter()
+                .any(|line| line.iter().all(|&i| board[i] == player))
+        };
+        match (wins(Player::X), wins(Player::O)) {
+            (true, true) => return Err(GameError::IllegalBoard),
+            (true, false) if x_count == o_count => return Err(GameError::IllegalBoard),
+            (false, true) if x_count != o_count => return Err(GameError::IllegalBoard),
+            _ => {}
+        }
+
+        let mut game = Game {
+            board,
+            ..Game::new()
+        };
+        game.turn = game.derive_turn();
+        Ok(game)
+    }
+
+    /// Returns the number of empty cells.
+    pub fn remaining(&self) -> usize {
+        self.empty_cells().count()
+    }
+
+    /// Returns the number of moves played so far, i.e. the number of occupied cells.
+    pub fn move_count(&self) -> usize {
+        self.board.iter().filter(|&&p| p != Player::None).count()
+    }
+
+    /// Packs the board into an integer, two bits per cell starting from the lowest bits: `00` for an empty cell, `01`
+    /// for `X` and `10` for `O`.
+    pub fn pack(&self) -> u32 {
+        self.board
+            .iter()
+            .enumerate()
+            .fold(0, |packed, (i, &player)| {
+                let bits = match player {
+                    Player::None => 0b00,
+                    Player::X => 0b01,
+                    Player::O => 0b10,
+                };
+                packed | bits << (2 * i)
+            })
+    }
+
+    /// Creates a game from a board packed with `pack`.
+    ///
+    /// Returns `GameError::IllegalBoard` if a cell is encoded as `11`, bits are set past the last cell, or the board
+    /// can't be reached by playing (see `from_board`).
+    pub fn unpack(packed: u32) -> Result<Game, GameError> {
+        if packed >> (2 * BOARD_SIZE * BOARD_SIZE) != 0 {
+            return Err(GameError::IllegalBoard);
+        }
+        let mut board = [Player::None; BOARD_SIZE * BOARD_SIZE];
+        for (i, cell) in board.iter_mut().enumerate() {
+            *cell = match packed >> (2 * i) & 0b11 {
+                0b00 => Player::None,
+                0b01 => Player::X,
+                0b10 => Player::O,
+                _ => return Err(GameError::IllegalBoard),
+            };
+        }
+        Game::from_board(board)
+    }
+
+    /// Returns the board split into rows, from top to bottom.
+    pub fn rows(&self) -> Vec<Vec<Player>> {
+        self.board
+            .chunks(BOARD_SIZE)
+            .map(<[Player]>::to_vec)
+            .collect()
+    }
+
+    /// Converts a row and a column into the index of a cell on the board.
+    pub fn cell_index(row: usize, col: usize) -> Result<usize, GameError> {
+        if row >= BOARD_SIZE || col >= BOARD_SIZE {
+            return Err(GameError::OutOfBounds { row, col });
+        }
+        Ok(row * BOARD_SIZE + col)
+    }
+
+    /// Ensures that the index of a cell is on the board.
+    pub fn check_cell(cell: usize) -> Result<usize, GameError> {
+        if cell >= BOARD_SIZE * BOARD_SIZE {
+            return Err(GameError::CellOutOfBounds(cell));
+        }
+        Ok(cell)
+    }
+
+    /// Returns the player whose turn it should be given the board: `X` if both players made as many moves, `O`
+    /// otherwise.
+    pub fn derive_turn(&self) -> Player {
+        let count = |player| self.board.iter().filter(|&&p| p == player).count();
+        if count(Player::X) == count(Player::O) {
+            Player::X
+        } else {
+            Player::O
         }
     }
 
     /// Plays a move on the board.
     pub fn play(&mut self, player: Player, index: usize) -> Result<(), GameError> {
+        debug_assert_eq!(self.turn, self.derive_turn(), "corrupted turn");
+
+        if self.is_over() {
+            return Err(GameError::GameOver);
+        }
+
+        // `None` marks an empty cell, it can't be played.
+        if player == Player::None {
+            return Err(GameError::InvalidPlayer);
+        }
+
         if self.turn != player {
             return Err(GameError::NotYourTurn);
         }
@@ -65,28 +395,193 @@ impl Game {
         *cell = player;
 
         // Switch turns.
-        self.turn = match player {
-            Player::X => Player::O,
-            Player::O => Player::X,
-            Player::None => Player::None,
-        };
+        self.turn = player.opponent();
         Ok(())
     }
 
-    /// Get the winner of the game. Returns `None` if there is no winner yet.
-    pub fn winner(&self) -> Option<Player> {
+    /// Get the winner of the game along with the line they completed. Returns `None` if there is no winner yet.
+    pub fn win_details(&self) -> Option<(Player, [usize; 3])> {
         for combination in &WINNING_COMBINATIONS {
             let player = self.board[combination[0]];
             if player != Player::None && combination.iter().all(|&i| self.board[i] == player) {
-                return Some(player);
+                return Some((player, *combination));
             }
         }
         None
     }
 
-    /// Checks if the game is over. A game is over if there is a winner or if the board is full.
+    /// Get the winner of the game. Returns `None` if there is no winner yet.
+    pub fn winner(&self) -> Option<Player> {
+        self.win_details().map(|(player, _)| player)
+    }
+
+    /// Get the cells of the winning line. Returns `None` if there is no winner yet.
+    pub fn winning_line(&self) -> Option<[usize; 3]> {
+        self.win_details().map(|(_, line)| line)
+    }
+
+    /// Get the winning combinations the player could still complete, i.e. those without any mark of the opponent.
+    pub fn open_lines(&self, player: Player) -> Vec<[usize; 3]> {
+        if player == Player::None {
+            return vec![];
+        }
+        let opponent = player.opponent();
+        WINNING_COMBINATIONS
+            .iter()
+            .filter(|combination| combination.iter().all(|&i| self.board[i] != opponent))
+            .copied()
+            .collect()
+    }
+
+    /// Get a cell that immediately completes a line for the player. Returns `None` if there is no such cell.
+    pub fn winning_move(&self, player: Player) -> Option<usize> {
+        if player == Player::None {
+            return None;
+        }
+        WINNING_COMBINATIONS.iter().find_map(|combination| {
+            let mut empty = combination
+                .iter()
+                .filter(|&&i| self.board[i] == Player::None);
+            let cell = *empty.next()?;
+            let owned = combination
+                .iter()
+                .filter(|&&i| self.board[i] == player)
+                .count();
+            (empty.next().is_none() && owned == 2).then_some(cell)
+        })
+    }
+
+    /// Checks if the game is over. A game is over if there is a winner, if the board is full or if it was abandoned.
     pub fn is_over(&self) -> bool {
-        self.winner().is_some() || self.board.iter().all(|&p| p != Player::None)
+        self.result() != GameResult::InProgress
+    }
+
+    /// Ends the game before it is over, on behalf of the given player if any.
+    pub fn abandon(&mut self, by: Option<Player>) {
+        self.abandoned = true;
+        self.abandoned_by = by;
+    }
+
+    /// Returns the result of the game.
+    ///
+    /// This is the single place the board is scanned for the state of the game, callers needing more than one of
+    /// the winner and whether the game is over should match on it rather than call both.
+    pub fn result(&self) -> GameResult {
+        match self.win_details() {
+            Some((player, line)) => GameResult::Winner { player, line },
+            None if self.abandoned => GameResult::Abandoned {
+                by: self.abandoned_by,
+            },
+            None if self.board.iter().all(|&p| p != Player::None) => GameResult::Draw,
+            None => GameResult::InProgress,
+        }
+    }
+
+    /// Returns the best cell to play for the player whose turn it is, or `None` if the game is over.
+    ///
+    /// The whole game tree is searched with minimax, preferring the quickest win and the slowest loss.
+    pub fn best_move(&self) -> Option<usize> {
+        if self.is_over() {
+            return None;
+        }
+
+        let mut best = None;
+        let mut alpha = -MAX_SCORE;
+        for (cell, child) in self.children() {
+            let score = -child.negamax(-MAX_SCORE, -alpha);
+            if best.is_none() || score > alpha {
+                best = Some(cell);
+                alpha = score;
+            }
+        }
+        best
+    }
+
+    /// Returns the result the game would reach if both players played perfectly from now on.
+    pub fn evaluate(&self) -> GameResult {
+        let mut game = *self;
+        while let Some(cell) = game.best_move() {
+            game = game.with_move(cell);
+        }
+        game.result()
+    }
+
+    /// Returns the chances, in permille, that `X` wins, that `O` wins and that the game ends in a draw if both
+    /// players play uniformly at random from now on. The rounding error is attributed to the draw.
+    pub fn odds(&self) -> (u32, u32, u32) {
+        let (x_win, o_win, _) = self.rollouts();
+        let total = (1..=self.remaining() as u64).product::<u64>();
+        let x_win = (x_win * 1000 / total) as u32;
+        let o_win = (o_win * 1000 / total) as u32;
+        (x_win, o_win, 1000 - x_win - o_win)
+    }
+
+    /// Counts the outcomes of all the ways to finish the game, weighted so that every move is equally likely. The
+    /// weights of a position with `n` empty cells sum to `n!`.
+    fn rollouts(&self) -> (u64, u64, u64) {
+        let result = self.result();
+        if result != GameResult::InProgress {
+            let weight = (1..=self.remaining() as u64).product();
+            return match result {
+                GameResult::Winner {
+                    player: Player::X, ..
+                } => (weight, 0, 0),
+                GameResult::Winner { .. } => (0, weight, 0),
+                _ => (0, 0, weight),
+            };
+        }
+
+        self.children()
+            .iter()
+            .map(|(_, child)| child.rollouts())
+            .fold((0, 0, 0), |(x, o, d), (cx, co, cd)| {
+                (x + cx, o + co, d + cd)
+            })
+    }
+
+    /// Returns the score of the game for the player whose turn it is, using negamax with alpha-beta pruning.
+    fn negamax(&self, mut alpha: i32, beta: i32) -> i32 {
+        match self.result() {
+            // The last player to move won the game, the sooner the worse.
+            GameResult::Winner { .. } => return -(self.remaining() as i32 + 1),
+            GameResult::InProgress => {}
+            _ => return 0,
+        }
+
+        let mut best = -MAX_SCORE;
+        for (_, child) in self.children() {
+            let score = -child.negamax(-beta, -alpha);
+            best = best.max(score);
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+        best
+    }
+
+    /// Returns the positions the player whose turn it is can reach in one move, along with the cell they played.
+    /// Returns no position once the game is over.
+    pub fn children(&self) -> Vec<(usize, Game)> {
+        if self.is_over() {
+            return vec![];
+        }
+        self.empty_cells()
+            .map(|cell| (cell, self.with_move(cell)))
+            .collect()
+    }
+
+    /// Returns the indexes of the empty cells.
+    fn empty_cells(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.board.len()).filter(|&i| self.board[i] == Player::None)
+    }
+
+    /// Returns a copy of the game after the player whose turn it is played on the given empty cell.
+    fn with_move(&self, cell: usize) -> Game {
+        let mut game = *self;
+        game.board[cell] = self.turn;
+        game.turn = self.turn.opponent();
+        game
     }
 
     #[cfg(test)]
@@ -106,6 +601,122 @@ impl Game {
 mod tests {
     use super::*;
 
+    #[test]
+    fn opponent() {
+        assert_eq!(Player::X.opponent(), Player::O);
+        assert_eq!(Player::O.opponent(), Player::X);
+        assert_eq!(Player::None.opponent(), Player::None);
+    }
+
+    #[test]
+    fn as_symbol() {
+        assert_eq!(Player::X.as_symbol(), 'X');
+        assert_eq!(Player::O.as_symbol(), 'O');
+        assert_eq!(Player::None.as_symbol(), ' ');
+    }
+
+    #[test]
+    fn children() {
+        use Player::{None as E, O, X};
+        let game = Game::from_board([X, O, E, E, X, E, E, E, E]).unwrap();
+        let children = game.children();
+        assert_eq!(children.len(), 6);
+        for (cell, child) in children {
+            assert_eq!(game.board[cell], E);
+            assert_eq!(child.board[cell], O);
+            assert_eq!(child.turn, X);
+        }
+
+        let over = Game::from_board([X, X, X, O, O, E, E, E, E]).unwrap();
+        assert!(over.children().is_empty());
+    }
+
+    #[test]
+    fn move_count() {
+        let mut game = Game::new();
+        assert_eq!(game.move_count(), 0);
+        game.play(Player::X, 4).unwrap();
+        game.play(Player::O, 0).unwrap();
+        assert_eq!(game.move_count(), 2);
+        assert_eq!(game.remaining(), 7);
+    }
+
+    #[test]
+    fn display_and_parse() {
+        assert_eq!(Player::X.to_string(), "X");
+        assert_eq!(Player::O.to_string(), "O");
+
+        assert_eq!("X".parse(), Ok(Player::X));
+        assert_eq!("x".parse(), Ok(Player::X));
+        assert_eq!("O".parse(), Ok(Player::O));
+        assert_eq!("o".parse(), Ok(Player::O));
+        for invalid in ["", " ", "0", "XO", "none"] {
+            assert_eq!(invalid.parse::<Player>(), Err(GameError::InvalidPlayer));
+        }
+    }
+
+    #[test]
+    fn player_json() {
+        let mut game = Game::new();
+        game.play(Player::X, 0).unwrap();
+        game.play(Player::O, 4).unwrap();
+
+        let json = cosmwasm_std::to_json_string(&game.board).unwrap();
+        assert_eq!(json, r#"["x",null,null,null,"o",null,null,null,null]"#);
+
+        let board: [Player; 9] = cosmwasm_std::from_json(json).unwrap();
+        assert_eq!(board, game.board);
+
+        let decoded: Game =
+            cosmwasm_std::from_json(cosmwasm_std::to_json_vec(&game).unwrap()).unwrap();
+        assert_eq!(decoded, game);
+        assert!(cosmwasm_std::from_json::<Player>(r#""Z""#).is_err());
+    }
+
+    #[test]
+    fn legacy_player_json() {
+        let board: [Player; 9] = cosmwasm_std::from_json(
+            r#"["X","None","None","None","O","None","None","None","None"]"#,
+        )
+        .unwrap();
+        let mut game = Game::new();
+        game.play(Player::X, 0).unwrap();
+        game.play(Player::O, 4).unwrap();
+        assert_eq!(board, game.board);
+
+        // A game stored by an earlier version still loads, and is written back in the current encoding.
+        let stored = r#"{"board":["X","None","None","None","O","None","None","None","None"],"turn":"X","created_at":null,"completed_at":null}"#;
+        let decoded: Game = cosmwasm_std::from_json(stored).unwrap();
+        assert_eq!(decoded, game);
+        assert_eq!(
+            cosmwasm_std::to_json_string(&decoded.turn).unwrap(),
+            r#""x""#
+        );
+    }
+
+    #[test]
+    fn result_winner() {
+        let line = [0, 1, 2];
+        assert_eq!(
+            GameResult::Winner {
+                player: Player::O,
+                line
+            }
+            .winner(),
+            Some(Player::O)
+        );
+        assert_eq!(
+            GameResult::Abandoned {
+                by: Some(Player::O)
+            }
+            .winner(),
+            Some(Player::X)
+        );
+        assert_eq!(GameResult::Abandoned { by: None }.winner(), None);
+        assert_eq!(GameResult::Draw.winner(), None);
+        assert_eq!(GameResult::InProgress.winner(), None);
+    }
+
     #[test]
     fn game_creation() {
         let game = Game::new();
@@ -114,6 +725,25 @@ mod tests {
         assert_eq!(game.turn, Player::X);
     }
 
+    #[test]
+    fn derive_turn() {
+        use Player::{None as E, O, X};
+        assert_eq!(Game::new().derive_turn(), X);
+
+        let balanced = Game {
+            board: [X, O, E, E, X, E, E, E, O],
+            ..Game::new()
+        };
+        assert_eq!(balanced.derive_turn(), X);
+
+        let unbalanced = Game {
+            board: [X, O, E, E, X, E, E, E, E],
+            ..Game::new()
+        };
+        assert_eq!(unbalanced.derive_turn(), O);
+        assert_ne!(unbalanced.turn, unbalanced.derive_turn());
+    }
+
     #[test]
     fn invalid_move() {
         let mut game = Game::new();
@@ -124,6 +754,190 @@ mod tests {
         assert_eq!(game.play(Player::O, 8), Ok(()));
     }
 
+    #[test]
+    fn rows() {
+        let rows = Game::new().rows();
+        assert_eq!(rows.len(), 3);
+        assert!(rows.iter().all(|row| row == &[Player::None; 3]));
+
+        let mut game = Game::new();
+        game.play(Player::X, 5).unwrap();
+        assert_eq!(game.rows()[1], [Player::None, Player::None, Player::X]);
+    }
+
+    #[test]
+    fn cell_index() {
+        assert_eq!(Game::cell_index(0, 0), Ok(0));
+        assert_eq!(Game::cell_index(1, 1), Ok(4));
+        assert_eq!(Game::cell_index(2, 1), Ok(7));
+        assert_eq!(
+            Game::cell_index(0, 3),
+            Err(GameError::OutOfBounds { row: 0, col: 3 })
+        );
+        assert_eq!(
+            Game::cell_index(3, 0),
+            Err(GameError::OutOfBounds { row: 3, col: 0 })
+        );
+    }
+
+    #[test]
+    fn check_cell() {
+        assert_eq!(Game::check_cell(0), Ok(0));
+        assert_eq!(Game::check_cell(8), Ok(8));
+        assert_eq!(Game::check_cell(9), Err(GameError::CellOutOfBounds(9)));
+    }
+
+    #[test]
+    fn invalid_player() {
+        let mut game = Game::new();
+
+        assert_eq!(game.play(Player::None, 0), Err(GameError::InvalidPlayer));
+        assert_eq!(game.board, [Player::None; 9]);
+        assert_eq!(game.turn, Player::X);
+    }
+
+    #[test]
+    fn from_preset() {
+        use Player::{None as E, O, X};
+
+        let game = Game::from_preset(&[(0, X), (4, O), (8, X)]).unwrap();
+        assert_eq!(game.board, [X, E, E, E, O, E, E, E, X]);
+        assert_eq!(game.turn, O);
+
+        let game = Game::from_preset(&[]).unwrap();
+        assert_eq!(game, Game::new());
+
+        // Out of the board.
+        assert_eq!(Game::from_preset(&[(9, X)]), None);
+        // Played twice.
+        assert_eq!(Game::from_preset(&[(0, X), (0, O)]), None);
+        // Played by nobody.
+        assert_eq!(Game::from_preset(&[(0, X), (1, E)]), None);
+        // Unbalanced.
+        assert_eq!(Game::from_preset(&[(0, O)]), None);
+        assert_eq!(Game::from_preset(&[(0, X), (1, X)]), None);
+        // Already over.
+        assert_eq!(
+            Game::from_preset(&[(0, X), (3, O), (1, X), (4, O), (2, X)]),
+            None
+        );
+    }
+
+    #[test]
+    fn pack_and_unpack() {
+        use Player::{None as E, O, X};
+        assert_eq!(Game::new().pack(), 0);
+        let game = Game::from_board([X, O, E, E, E, E, E, E, E]).unwrap();
+        assert_eq!(game.pack(), 0b10_01);
+
+        let boards = [
+            [E, E, E, E, E, E, E, E, E],
+            [X, E, E, E, E, E, E, E, E],
+            [X, O, E, E, X, E, E, E, E],
+            [X, X, X, O, O, E, E, E, E],
+            [X, O, X, X, O, O, O, X, X],
+            [O, O, O, X, X, E, X, E, E],
+        ];
+        for board in boards {
+            let game = Game::from_board(board).unwrap();
+            assert_eq!(Game::unpack(game.pack()), Ok(game));
+        }
+
+        // A cell encoded as 11, bits past the board, and an unreachable board.
+        assert_eq!(Game::unpack(0b11), Err(GameError::IllegalBoard));
+        assert_eq!(Game::unpack(1 << 18), Err(GameError::IllegalBoard));
+        assert_eq!(Game::unpack(0b10), Err(GameError::IllegalBoard));
+    }
+
+    #[test]
+    fn from_board() {
+        use Player::{None as E, O, X};
+        let game = Game::from_board([X, O, E, E, X, E, E, E, E]).unwrap();
+        assert_eq!(game.turn, O);
+        assert!(!game.is_over());
+
+        // Both players completed a line.
+        assert_eq!(
+            Game::from_board([X, X, X, O, O, O, E, E, E]),
+            Err(GameError::IllegalBoard)
+        );
+        // O played twice in a row.
+        assert_eq!(
+            Game::from_board([O, O, E, E, E, E, E, E, E]),
+            Err(GameError::IllegalBoard)
+        );
+        // X kept playing after O won.
+        assert_eq!(
+            Game::from_board([O, O, O, X, X, E, X, X, E]),
+            Err(GameError::IllegalBoard)
+        );
+    }
+
+    #[test]
+    fn open_lines() {
+        use Player::{None as E, O, X};
+        let game = Game::from_board([X, O, E, E, X, E, E, E, E]).unwrap();
+
+        // The top row and the middle column are blocked by O.
+        assert_eq!(
+            game.open_lines(X),
+            vec![
+                [3, 4, 5],
+                [6, 7, 8],
+                [0, 3, 6],
+                [2, 5, 8],
+                [0, 4, 8],
+                [2, 4, 6]
+            ]
+        );
+        assert_eq!(game.open_lines(O), vec![[6, 7, 8], [2, 5, 8]]);
+        assert!(game.open_lines(E).is_empty());
+
+        assert_eq!(Game::new().open_lines(O).len(), 8);
+    }
+
+    #[test]
+    fn winning_move() {
+        use Player::{None as E, O, X};
+        // X completes the top row.
+        let game = Game::from_board([X, X, E, O, O, E, E, E, E]).unwrap();
+        assert_eq!(game.winning_move(X), Some(2));
+        assert_eq!(game.winning_move(O), Some(5));
+
+        // O has to block the diagonal.
+        let game = Game::from_board([X, O, E, E, X, E, E, E, E]).unwrap();
+        assert_eq!(game.winning_move(X), Some(8));
+        assert_eq!(game.winning_move(O), None);
+
+        assert_eq!(Game::new().winning_move(X), None);
+        assert_eq!(game.winning_move(E), None);
+    }
+
+    #[test]
+    fn result_agrees_with_helpers() {
+        use Player::{None as E, O, X};
+        let mut abandoned = Game::from_board([X, O, E, E, X, E, E, E, E]).unwrap();
+        abandoned.abandon(Some(O));
+        let games = [
+            Game::new(),
+            Game::from_board([X, O, E, E, X, E, E, E, E]).unwrap(),
+            Game::from_board([X, X, X, O, O, E, E, E, E]).unwrap(),
+            Game::from_board([X, O, X, X, O, O, O, X, X]).unwrap(),
+            abandoned,
+        ];
+        for game in games {
+            let result = game.result();
+            assert_eq!(game.is_over(), result != GameResult::InProgress);
+            assert_eq!(
+                game.winner(),
+                match result {
+                    GameResult::Winner { player, .. } => Some(player),
+                    _ => None,
+                }
+            );
+        }
+    }
+
     #[test]
     fn not_your_turn() {
         let mut game = Game::new();
@@ -134,6 +948,116 @@ mod tests {
         assert_eq!(game.play(Player::O, 4), Ok(()));
     }
 
+    #[test]
+    fn best_move_takes_win() {
+        use Player::{None as E, O, X};
+        // X can win on the top row, or block O on the middle row.
+        let game = Game {
+            board: [X, X, E, O, O, E, E, E, E],
+            turn: X,
+            ..Game::new()
+        };
+        assert_eq!(game.best_move(), Some(2));
+    }
+
+    #[test]
+    fn best_move_blocks_opponent() {
+        use Player::{None as E, O, X};
+        // X threatens the diagonal, O must block it.
+        let game = Game {
+            board: [X, E, E, E, X, E, O, E, E],
+            turn: O,
+            ..Game::new()
+        };
+        assert_eq!(game.best_move(), Some(8));
+    }
+
+    #[test]
+    fn best_move_edge_cases() {
+        let game = Game::new();
+        assert!(game.best_move().is_some());
+
+        let mut game = Game::new();
+        for (player, cell) in [
+            (Player::X, 0),
+            (Player::O, 3),
+            (Player::X, 1),
+            (Player::O, 4),
+            (Player::X, 2),
+        ] {
+            game.play(player, cell).unwrap();
+        }
+        assert_eq!(game.best_move(), None);
+    }
+
+    #[test]
+    fn evaluate() {
+        use Player::{None as E, O, X};
+        // Tic-tac-toe is a draw with perfect play.
+        assert_eq!(Game::new().evaluate(), GameResult::Draw);
+
+        // X forks on the bottom left corner and wins whatever O does.
+        let game = Game {
+            board: [X, O, E, E, X, E, E, E, O],
+            turn: X,
+            ..Game::new()
+        };
+        assert!(matches!(
+            game.evaluate(),
+            GameResult::Winner { player: X, .. }
+        ));
+
+        // A finished game evaluates to its result.
+        let game = Game {
+            board: [X, X, X, O, O, E, E, E, E],
+            turn: O,
+            ..Game::new()
+        };
+        assert_eq!(game.evaluate(), game.result());
+    }
+
+    #[test]
+    fn odds() {
+        use Player::{None as E, O, X};
+        // With random play, the first player is the most likely to win.
+        let (x_win, o_win, draw) = Game::new().odds();
+        assert_eq!(x_win + o_win + draw, 1000);
+        assert!(x_win > o_win && o_win > draw);
+
+        // A won game is certain.
+        let game = Game {
+            board: [O, O, O, X, X, E, X, E, E],
+            turn: X,
+            ..Game::new()
+        };
+        assert_eq!(game.odds(), (0, 1000, 0));
+
+        // X completes the top row half of the time, otherwise O is forced to block and draws.
+        let game = Game {
+            board: [X, X, E, O, O, X, X, O, E],
+            turn: O,
+            ..Game::new()
+        };
+        assert_eq!(game.odds(), (500, 0, 500));
+    }
+
+    #[test]
+    fn abandoned_game() {
+        let mut game = Game::new();
+        game.play(Player::X, 0).unwrap();
+
+        game.abandon(Some(Player::O));
+        assert!(game.is_over());
+        assert_eq!(game.winner(), None);
+        assert_eq!(
+            game.result(),
+            GameResult::Abandoned {
+                by: Some(Player::O)
+            }
+        );
+        assert_eq!(game.best_move(), None);
+    }
+
     #[test]
     fn game_over_with_draw() {
         let mut game = Game::new();
@@ -155,9 +1079,12 @@ mod tests {
         assert!(!game.is_over());
         assert_eq!(game.play(Player::O, 7), Ok(()));
         assert!(!game.is_over());
+        assert_eq!(game.result(), GameResult::InProgress);
         assert_eq!(game.play(Player::X, 1), Ok(()));
 
         assert!(game.is_over());
+        assert_eq!(game.result(), GameResult::Draw);
+        assert_eq!(game.winning_line(), None);
     }
 
     #[test]
@@ -177,6 +1104,14 @@ mod tests {
 
         assert!(game.is_over());
         assert_eq!(game.winner().unwrap(), Player::X);
+        assert_eq!(game.winning_line(), Some([0, 1, 2]));
+        assert_eq!(
+            game.result(),
+            GameResult::Winner {
+                player: Player::X,
+                line: [0, 1, 2]
+            }
+        );
     }
 
     #[test]
@@ -198,5 +1133,37 @@ mod tests {
 
         assert!(game.is_over());
         assert_eq!(game.winner().unwrap(), Player::O);
+        assert_eq!(game.win_details(), Some((Player::O, [3, 4, 5])));
+    }
+
+    mod invariants {
+        use super::*;
+        use proptest::prelude::*;
+
+        fn completes_line(game: &Game, player: Player) -> bool {
+            WINNING_COMBINATIONS
+                .iter()
+                .any(|line| line.iter().all(|&i| game.board[i] == player))
+        }
+
+        proptest! {
+            #[test]
+            fn play_keeps_invariants(cells in prop::collection::vec(0..BOARD_SIZE * BOARD_SIZE, 0..20)) {
+                let mut game = Game::new();
+                for cell in cells {
+                    let was_over = game.is_over();
+                    let played = game.play(game.turn, cell);
+                    if was_over {
+                        prop_assert_eq!(played, Err(GameError::GameOver));
+                    }
+
+                    prop_assert!(!(completes_line(&game, Player::X) && completes_line(&game, Player::O)));
+                    prop_assert_eq!(game.turn, game.derive_turn());
+                    let full = game.board.iter().all(|&p| p != Player::None);
+                    prop_assert_eq!(game.is_over(), game.winner().is_some() || full);
+                    prop_assert!(Game::from_board(game.board).is_ok());
+                }
+            }
+        }
     }
 }
@@ -5,9 +5,9 @@ mod contract;
 #[cfg(test)]
 mod cw_multitests;
 mod error;
-mod game;
-mod msg;
-mod state;
+pub mod game;
+pub mod msg;
+pub mod state;
 
 #[entry_point]
 pub fn instantiate(
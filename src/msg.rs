@@ -1,28 +1,76 @@
-use cosmwasm_schema::cw_serde;
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Addr, Timestamp};
 
-use crate::game::{Game, Player};
+use crate::{
+    game::{Game, GameResult, Player},
+    state::{Config, GameStatus, PlayerStats},
+};
 
 #[cw_serde]
-pub struct InstantiateMsg {}
+pub struct InstantiateMsg {
+    /// The address allowed to update the configuration. Defaults to the instantiator.
+    pub admin: Option<String>,
+    /// The number of seconds after which a pending invitation can be expired. Defaults to a day.
+    pub invite_timeout: Option<u64>,
+    /// The number of seconds a player has to make a move. Defaults to a day.
+    pub move_timeout: Option<u64>,
+    /// Whether finished games are kept in the history of the players. Defaults to true.
+    pub store_history: Option<bool>,
+    /// The maximum number of games in progress a player can have at once. Unlimited if not set.
+    pub max_active_per_player: Option<u32>,
+    /// The number of illegal moves in a row after which a player forfeits the game. Illegal moves are rejected without
+    /// being counted if not set.
+    pub max_illegal_moves: Option<u32>,
+    /// The number of seconds a host has to wait before inviting the same guest again. Defaults to none.
+    pub invite_cooldown: Option<u64>,
+}
 
 #[cw_serde]
 pub enum ExecuteMsg {
-    /// Invite a player to play a game.
+    /// Invite a player to play a game. If that player already invited the sender, their invitation is accepted
+    /// instead: the match target and the time bank can only repeat its terms, no message can be sent, and the symbol
+    /// is requested as on `Accept`.
     Invite {
         /// The address of the player to invite.
         guest: String,
+        /// The number of games a player must win to win the match. A single game is played if not set.
+        match_target: Option<u32>,
+        /// The symbol the host wants to play. Assigned from the addresses if not set.
+        symbol: Option<Player>,
+        /// An optional message for the guest, at most 256 characters long.
+        message: Option<String>,
+        /// The total number of seconds each player can spend on their moves of a game. When set, a player who runs
+        /// out of time loses on `ClaimTimeout` instead of after the move timeout.
+        time_bank: Option<u64>,
+    },
+    /// Invite several players at once, at most 20. The players who can't be invited, e.g. because a game is in
+    /// progress with them, are skipped rather than failing the whole batch.
+    InviteMany {
+        /// The addresses of the players to invite.
+        guests: Vec<String>,
     },
     /// Accept an invitation to play a game.
     Accept {
         /// The address of the player who invited you.
         host: String,
+        /// The moves to place on the board before the game starts, as (cell, player) pairs.
+        preset: Option<Vec<(usize, Player)>>,
+        /// The symbol the guest wants to play. Any symbol the host did not choose is accepted if not set.
+        requested_symbol: Option<Player>,
+    },
+    /// Expire a pending invitation that the guest did not respond to in time.
+    ExpireInvite {
+        /// The address of the invited player.
+        guest: String,
     },
     /// Reject an invitation to play a game.
     Reject {
         /// The address of the player who invited you.
         host: String,
+        /// An optional reason for the rejection, at most 128 characters long.
+        reason: Option<String>,
     },
-    /// Play a move in the game.
+    /// Play a move in the game. A player playing against themselves must use `PlaySequence` instead.
     Play {
         /// The address of the host of the game.
         host: String,
@@ -30,20 +78,496 @@ pub enum ExecuteMsg {
         guest: String,
         /// The cell to play in.
         cell: usize,
+        /// A number greater than the one sent with the previous move of the sender in the games of the pair, if any,
+        /// so that a rebroadcast move is rejected rather than played twice.
+        nonce: Option<u64>,
+    },
+    /// Play a move in the game at the given row and column.
+    PlayAt {
+        /// The address of the host of the game.
+        host: String,
+        /// The address of the guest of the game.
+        guest: String,
+        /// The row to play in, starting from 0.
+        row: usize,
+        /// The column to play in, starting from 0.
+        col: usize,
+    },
+    /// Play a sequence of moves in the game, alternating players according to the turn.
+    ///
+    /// The sender must be the player responsible for every move of the sequence, which can't be empty. When illegal
+    /// moves are counted, an illegal move ends the sequence and the moves before it are kept.
+    PlaySequence {
+        /// The address of the host of the game.
+        host: String,
+        /// The address of the guest of the game.
+        guest: String,
+        /// The cells to play in, in order.
+        cells: Vec<usize>,
+    },
+    /// Hand over the sender's seat in a game in progress to another address.
+    TransferSeat {
+        /// The address of the host of the game.
+        host: String,
+        /// The address of the guest of the game.
+        guest: String,
+        /// The address taking over the sender's seat.
+        new_addr: String,
+    },
+    /// Resign from a game in progress.
+    Resign {
+        /// The address of the host of the game.
+        host: String,
+        /// The address of the guest of the game.
+        guest: String,
+    },
+    /// Concede the match in progress, giving the win of the match to the opponent.
+    ConcedeMatch {
+        /// The address of the host of the game.
+        host: String,
+        /// The address of the guest of the game.
+        guest: String,
+    },
+    /// Win a game in which the opponent did not play within the move timeout, or ran out of their time bank.
+    ClaimTimeout {
+        /// The address of the host of the game.
+        host: String,
+        /// The address of the guest of the game.
+        guest: String,
+    },
+    /// Complete a current game that is already over but was not completed, e.g. after an import.
+    ClaimResult {
+        /// The address of the host of the game.
+        host: String,
+        /// The address of the guest of the game.
+        guest: String,
+    },
+    /// End a game in progress without a winner, along with its match if any. Only the admin can do this.
+    ForceEndGame {
+        /// The address of the host of the game.
+        host: String,
+        /// The address of the guest of the game.
+        guest: String,
+    },
+    /// Store the games between two players exported from another contract. Only the admin can do this.
+    ImportGame {
+        /// The address of the host of the game.
+        host: String,
+        /// The address of the guest of the game.
+        guest: String,
+        /// The symbol played by the host.
+        host_role: Player,
+        /// The game in progress, if any.
+        current: Option<Game>,
+        /// The completed games, the oldest first.
+        completed: Vec<Game>,
+        /// Whether to replace the games of the pair even if a game is in progress.
+        force: bool,
+    },
+    /// Delete all the games between two players. Only the admin can do this.
+    PurgePair {
+        /// The address of the host of the game.
+        host: String,
+        /// The address of the guest of the game.
+        guest: String,
+        /// Whether to purge the pair even if a game is in progress.
+        force: bool,
+    },
+    /// Set the nickname shown alongside the sender's address.
+    SetNickname {
+        /// The nickname, at most 32 characters long.
+        nickname: String,
+    },
+    /// Post a chat message to the other player of a game. Only the last 20 messages are kept.
+    PostMessage {
+        /// The address of the host of the game.
+        host: String,
+        /// The address of the guest of the game.
+        guest: String,
+        /// The message, at most 280 characters long.
+        text: String,
+    },
+    /// Delete the completed games between the host and the guest. Both players must send this message: the first one
+    /// requests the deletion and the second one confirms it. The global statistics are kept.
+    ClearMyHistory {
+        /// The address of the host of the pair.
+        host: String,
+        /// The address of the guest of the pair.
+        guest: String,
+    },
+    /// Update the configuration of the contract. Only the admin can do this.
+    UpdateConfig {
+        /// The new number of seconds after which a pending invitation can be expired.
+        invite_timeout: Option<u64>,
+        /// The new number of seconds a player has to make a move.
+        move_timeout: Option<u64>,
     },
 }
 
+/// The value of the `action` attribute of the events emitted by the contract.
+///
+/// The values are the snake_case names of the variants and are stable, so that indexers can match them.
 #[cw_serde]
+#[derive(Copy)]
+pub enum Action {
+    Instantiate,
+    Invite,
+    InviteMany,
+    MutualAccept,
+    ExpireInvite,
+    AcceptInvite,
+    RejectInvite,
+    Play,
+    IllegalMove,
+    PlaySequence,
+    TransferSeat,
+    Resign,
+    ClaimTimeout,
+    ClaimResult,
+    ForceEnd,
+    ImportGame,
+    PurgePair,
+    SetNickname,
+    PostMessage,
+    ClearHistory,
+    UpdateConfig,
+    GameOver,
+    MatchOver,
+    MatchConceded,
+}
+
+impl Action {
+    /// Returns the value of the attribute, the same as the serialization of the action.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Action::Instantiate => "instantiate",
+            Action::Invite => "invite",
+            Action::InviteMany => "invite_many",
+            Action::MutualAccept => "mutual_accept",
+            Action::ExpireInvite => "expire_invite",
+            Action::AcceptInvite => "accept_invite",
+            Action::RejectInvite => "reject_invite",
+            Action::Play => "play",
+            Action::IllegalMove => "illegal_move",
+            Action::PlaySequence => "play_sequence",
+            Action::TransferSeat => "transfer_seat",
+            Action::Resign => "resign",
+            Action::ClaimTimeout => "claim_timeout",
+            Action::ClaimResult => "claim_result",
+            Action::ForceEnd => "force_end",
+            Action::ImportGame => "import_game",
+            Action::PurgePair => "purge_pair",
+            Action::SetNickname => "set_nickname",
+            Action::PostMessage => "post_message",
+            Action::ClearHistory => "clear_history",
+            Action::UpdateConfig => "update_config",
+            Action::GameOver => "game_over",
+            Action::MatchOver => "match_over",
+            Action::MatchConceded => "match_conceded",
+        }
+    }
+}
+
+impl From<Action> for String {
+    fn from(action: Action) -> Self {
+        action.as_str().to_string()
+    }
+}
+
+#[cw_serde]
+#[derive(QueryResponses)]
 pub enum QueryMsg {
+    /// Get the configuration of the contract.
+    #[returns(Config)]
+    Config {},
+    /// Get the name and version of the contract.
+    #[returns(cw2::ContractVersion)]
+    ContractInfo {},
+    /// Get the number of games completed across all players.
+    #[returns(TotalGamesResponse)]
+    TotalGames {},
+    /// Get the statistics of the games completed across all players.
+    #[returns(GlobalStatsResponse)]
+    GlobalStats {},
+    /// Get the number of pairs of players who played or invited each other and were not purged.
+    #[returns(GamesCountResponse)]
+    GamesCount {},
     /// Get all the games between two players.
+    #[returns(GamesResponse)]
     Games {
         /// The address of the host of the game.
         host: String,
         /// The address of the guest of the game.
         guest: String,
     },
+    /// Check whether the host ever invited the guest, without loading their games.
+    #[returns(bool)]
+    PairExists {
+        /// The address of the host of the game.
+        host: String,
+        /// The address of the guest of the game.
+        guest: String,
+    },
+    /// Get all the games between several pairs of players, at most 20, in the order of the pairs. The games of a pair
+    /// are `None` if the players never invited each other.
+    #[returns(Vec<Option<GamesInfo>>)]
+    GamesBatch {
+        /// The addresses of the hosts and guests of the games.
+        pairs: Vec<(String, String)>,
+    },
+    /// Get the hex-encoded raw storage key of the games between two players, in whichever order they are stored.
+    #[returns(String)]
+    StorageKey {
+        /// The address of the host of the game.
+        host: String,
+        /// The address of the guest of the game.
+        guest: String,
+    },
     /// Get all the games for all players.
-    AllGamesList {},
+    #[returns(AllGamesListResponse)]
+    AllGamesList {
+        /// Whether to list the pairs of players in descending order. Defaults to ascending.
+        descending: Option<bool>,
+        /// Only list the pairs with a game created or completed at or after this time.
+        from: Option<Timestamp>,
+        /// Only list the pairs with a game created or completed at or before this time.
+        to: Option<Timestamp>,
+        /// Only list the pairs with at least this number of completed games.
+        min_completed: Option<u32>,
+        /// Only list the pairs after this one, as returned in `next_cursor` by a previous query.
+        start_after: Option<(String, String)>,
+        /// The maximum number of pairs to list, at most 30. All the pairs are listed if not set.
+        limit: Option<u32>,
+    },
+    /// Get the pairs of players with stored games, as (host, guest), without the games themselves.
+    #[returns(AllPairsResponse)]
+    AllPairs {
+        /// Only list the pairs after this one, as returned in `next_cursor` by a previous query.
+        start_after: Option<(String, String)>,
+        /// The maximum number of pairs to list. Defaults to 10, at most 30.
+        limit: Option<u32>,
+    },
+    /// Get the most recent completed game between two players.
+    #[returns(LastGameResponse)]
+    LastGame {
+        /// The address of the host of the game.
+        host: String,
+        /// The address of the guest of the game.
+        guest: String,
+    },
+    /// Get the address of the winner of the current game, or of the most recent completed game if no game is in
+    /// progress. A player who resigned or timed out gives the game to their opponent. Returns `None` if that game is a
+    /// draw, was ended by the admin or is not decided yet.
+    #[returns(Option<String>)]
+    Winner {
+        /// The address of the host of the game.
+        host: String,
+        /// The address of the guest of the game.
+        guest: String,
+    },
+    /// Get the completed game at the given index between two players, the oldest game being at index 0.
+    #[returns(CompletedGameResponse)]
+    CompletedGame {
+        /// The address of the host of the game.
+        host: String,
+        /// The address of the guest of the game.
+        guest: String,
+        /// The index of the game in the completed games.
+        index: usize,
+    },
+    /// Get the board of the current game between two players as rows, from top to bottom.
+    #[returns(Vec<Vec<Player>>)]
+    BoardRows {
+        /// The address of the host of the game.
+        host: String,
+        /// The address of the guest of the game.
+        guest: String,
+    },
+    /// Get the best move for the player whose turn it is in the current game.
+    #[returns(SuggestMoveResponse)]
+    SuggestMove {
+        /// The address of the host of the game.
+        host: String,
+        /// The address of the guest of the game.
+        guest: String,
+    },
+    /// Check whether it is the given player's turn in the current game. Returns `false` if there is no game in
+    /// progress or if the player is not involved.
+    #[returns(bool)]
+    IsMyTurn {
+        /// The address of the host of the game.
+        host: String,
+        /// The address of the guest of the game.
+        guest: String,
+        /// The address of the player.
+        player: String,
+    },
+    /// Get an overview of the games of a player.
+    #[returns(DashboardResponse)]
+    Dashboard {
+        /// The address of the player.
+        player: String,
+    },
+    /// Get the hosts with a pending invitation for the given guest.
+    #[returns(PendingInvitesResponse)]
+    PendingInvitesFor {
+        /// The address of the invited player.
+        guest: String,
+    },
+    /// Get the games of the pairs in which the given player is the guest.
+    #[returns(AllGamesListResponse)]
+    GamesAsGuest {
+        /// The address of the guest.
+        guest: String,
+    },
+    /// Get the addresses of the players the given player has a game in progress with.
+    #[returns(Vec<String>)]
+    ActiveOpponents {
+        /// The address of the player.
+        player: String,
+    },
+    /// Get all the games with the given status.
+    #[returns(AllGamesListResponse)]
+    GamesByStatus {
+        /// The status to filter by.
+        status: GameStatus,
+    },
+    /// Get the completed games with the given result, grouped by pair of players. Pairs without such a game are
+    /// left out.
+    #[returns(AllGamesListResponse)]
+    GamesByResult {
+        /// The result to filter by.
+        result: ResultFilter,
+    },
+    /// Get the result of the current game with perfect play from both players, and the best move to reach it.
+    #[returns(EvaluatePositionResponse)]
+    EvaluatePosition {
+        /// The address of the host of the game.
+        host: String,
+        /// The address of the guest of the game.
+        guest: String,
+    },
+    /// Get the statistics of a player.
+    #[returns(PlayerStats)]
+    Stats {
+        /// The address of the player.
+        player: String,
+    },
+    /// Get the nickname of a player, if they set one.
+    #[returns(NicknameResponse)]
+    Nickname {
+        /// The address of the player.
+        player: String,
+    },
+    /// Get the chat messages posted by two players, as (sender, text) pairs from the oldest to the newest.
+    #[returns(Vec<(Addr, String)>)]
+    Chat {
+        /// The address of the host of the game.
+        host: String,
+        /// The address of the guest of the game.
+        guest: String,
+    },
+    /// Get the chances of each outcome of the current game between two players, if both play at random.
+    #[returns(OddsResponse)]
+    Odds {
+        /// The address of the host of the game.
+        host: String,
+        /// The address of the guest of the game.
+        guest: String,
+    },
+    /// Get the winning combinations the given player could still complete in the current game between two players.
+    #[returns(Vec<[usize; 3]>)]
+    Threats {
+        /// The address of the host of the game.
+        host: String,
+        /// The address of the guest of the game.
+        guest: String,
+        /// The symbol of the player.
+        player: Player,
+    },
+    /// Get a move completing a line for the player whose turn it is in the current game, or else a move blocking a line
+    /// of the opponent. Returns `None` if there is neither.
+    #[returns(Option<usize>)]
+    Hint {
+        /// The address of the host of the game.
+        host: String,
+        /// The address of the guest of the game.
+        guest: String,
+    },
+    /// Get the board of the current game packed into an integer, two bits per cell starting from the lowest bits:
+    /// `00` for an empty cell, `01` for `X` and `10` for `O`.
+    #[returns(u32)]
+    PackedBoard {
+        /// The address of the host of the game.
+        host: String,
+        /// The address of the guest of the game.
+        guest: String,
+    },
+    /// Get the number of empty cells of the current game, 0 if no game is in progress.
+    #[returns(usize)]
+    EmptyCount {
+        /// The address of the host of the game.
+        host: String,
+        /// The address of the guest of the game.
+        guest: String,
+    },
+    /// Get the symbol played by the given player in the games between two players.
+    #[returns(Player)]
+    MySymbol {
+        /// The address of the host of the game.
+        host: String,
+        /// The address of the guest of the game.
+        guest: String,
+        /// The address of the player.
+        player: String,
+    },
+    /// Get the symbol and address of the player who must play next in the current game.
+    #[returns(NextPlayerResponse)]
+    NextPlayer {
+        /// The address of the host of the game.
+        host: String,
+        /// The address of the guest of the game.
+        guest: String,
+    },
+    /// Check whether the given player can play in the given cell of the current game, without playing it.
+    #[returns(CanPlayResponse)]
+    CanPlay {
+        /// The address of the host of the game.
+        host: String,
+        /// The address of the guest of the game.
+        guest: String,
+        /// The address of the player.
+        player: String,
+        /// The cell to play in.
+        cell: usize,
+    },
+    /// Get the outcome of playing the given cell for the player whose turn it is in the current game, without
+    /// playing it.
+    #[returns(MovePreviewResponse)]
+    MovePreview {
+        /// The address of the host of the game.
+        host: String,
+        /// The address of the guest of the game.
+        guest: String,
+        /// The cell to play in.
+        cell: usize,
+    },
+    /// Check that the stored turn of the current game between two players matches the board.
+    #[returns(ValidateGameResponse)]
+    ValidateGame {
+        /// The address of the host of the game.
+        host: String,
+        /// The address of the guest of the game.
+        guest: String,
+    },
+    /// Get the scores of the match between two players. All scores are zero if the players are not in a match.
+    #[returns(MatchStatusResponse)]
+    MatchStatus {
+        /// The address of the host of the game.
+        host: String,
+        /// The address of the guest of the game.
+        guest: String,
+    },
 }
 
 /// The information about games between two players.
@@ -51,11 +575,58 @@ pub enum QueryMsg {
 pub struct GamesInfo {
     pub host: String,
     pub guest: String,
+    pub host_nickname: Option<String>,
+    pub guest_nickname: Option<String>,
+    /// A short reference to the current game, or to the next one if no game is in progress.
+    pub game_ref: String,
     pub host_role: Player,
     pub guest_role: Player,
+    pub status: GameStatus,
     pub pending_invitation: bool,
+    /// The message sent with the pending invitation, if any.
+    pub invite_message: Option<String>,
     pub current_game: Option<Game>,
     pub completed_games: Vec<Game>,
+    /// The completed games along with their result, in the same order as `completed_games`.
+    pub completed_results: Vec<CompletedGameInfo>,
+}
+
+/// A result to filter completed games by.
+#[cw_serde]
+#[derive(Copy)]
+pub enum ResultFilter {
+    /// The games in which the given player completed a line.
+    Winner(Player),
+    /// The games ended in a draw.
+    Draw,
+    /// The games ended before being over, by a player or by the admin.
+    Abandoned,
+}
+
+impl ResultFilter {
+    /// Returns whether the result of a game passes the filter.
+    pub fn matches(self, result: &GameResult) -> bool {
+        match (self, result) {
+            (ResultFilter::Winner(player), GameResult::Winner { player: winner, .. }) => {
+                player == *winner
+            }
+            (ResultFilter::Draw, GameResult::Draw) => true,
+            (ResultFilter::Abandoned, GameResult::Abandoned { .. }) => true,
+            _ => false,
+        }
+    }
+}
+
+/// A completed game along with its result and the address of its winner.
+#[cw_serde]
+pub struct CompletedGameInfo {
+    pub game: Game,
+    pub result: GameResult,
+    /// The address of the player who won the game, if any: the one who completed a line, or the opponent of the player
+    /// who resigned or timed out.
+    pub winner_addr: Option<String>,
+    /// The number of moves on the board when the game ended, including the preset ones.
+    pub move_count: usize,
 }
 
 /// All the games between two players.
@@ -68,4 +639,184 @@ pub struct GamesResponse {
 #[cw_serde]
 pub struct AllGamesListResponse {
     pub games: Vec<GamesInfo>,
+    /// The pair to start the next page after, if the page is full.
+    pub next_cursor: Option<(String, String)>,
+}
+
+/// A page of pairs of players, as (host, guest).
+#[cw_serde]
+pub struct AllPairsResponse {
+    pub pairs: Vec<(String, String)>,
+    /// The pair to start the next page after, if the page is full.
+    pub next_cursor: Option<(String, String)>,
+}
+
+/// The most recent completed game between two players, if any.
+#[cw_serde]
+pub struct LastGameResponse {
+    pub game: Option<Game>,
+    pub result: Option<GameResult>,
+}
+
+/// A completed game and its result.
+#[cw_serde]
+pub struct CompletedGameResponse {
+    pub game: Game,
+    pub result: GameResult,
+}
+
+/// The number of games completed across all players.
+#[cw_serde]
+pub struct TotalGamesResponse {
+    pub total_completed: u64,
+}
+
+/// The statistics of the games completed across all players.
+#[cw_serde]
+pub struct GlobalStatsResponse {
+    pub total_completed: u64,
+    /// The number of completed games that ended in a draw. Abandoned games are not counted.
+    pub total_draws: u64,
+}
+
+/// The number of pairs of players.
+#[cw_serde]
+pub struct GamesCountResponse {
+    pub count: u64,
+}
+
+/// The best move for the player whose turn it is.
+#[cw_serde]
+pub struct SuggestMoveResponse {
+    pub cell: usize,
+}
+
+/// The theoretical result of the current game.
+#[cw_serde]
+pub struct EvaluatePositionResponse {
+    /// The result of the game if both players play perfectly.
+    pub best_outcome: GameResult,
+    /// The best move for the player whose turn it is, if the game is not over.
+    pub best_move: Option<usize>,
+}
+
+/// The hosts with a pending invitation for a guest.
+#[cw_serde]
+pub struct PendingInvitesResponse {
+    pub hosts: Vec<String>,
+}
+
+/// The nickname of a player.
+#[cw_serde]
+pub struct NicknameResponse {
+    pub nickname: Option<String>,
+}
+
+/// The chances, in permille, of each outcome of a game.
+#[cw_serde]
+pub struct OddsResponse {
+    pub x_win: u32,
+    pub o_win: u32,
+    pub draw: u32,
+}
+
+/// The consistency of the stored turn of a game with its board.
+#[cw_serde]
+pub struct ValidateGameResponse {
+    /// The player whose turn it is according to the stored state.
+    pub turn: Player,
+    /// The player whose turn it should be according to the board.
+    pub expected_turn: Player,
+    pub valid: bool,
+}
+
+/// The player who must play next.
+#[cw_serde]
+pub struct NextPlayerResponse {
+    pub symbol: Player,
+    pub address: String,
+}
+
+/// Whether a move can be played.
+#[cw_serde]
+pub struct CanPlayResponse {
+    pub allowed: bool,
+    /// The reason the move can't be played, if it is not allowed.
+    pub reason: Option<String>,
+}
+
+/// The outcome of a move.
+#[cw_serde]
+pub struct MovePreviewResponse {
+    pub legal: bool,
+    /// Whether the move ends the game.
+    pub ends_game: bool,
+    /// The result of the game after the move, if it is legal.
+    pub result_if_played: Option<GameResult>,
+}
+
+/// The scores of a match between two players.
+#[cw_serde]
+pub struct MatchStatusResponse {
+    pub host_score: u32,
+    pub guest_score: u32,
+    /// The number of games a player must win to win the match, if the players are in a match.
+    pub target: Option<u32>,
+    pub match_over: bool,
+    /// The address of the player who won the match, if it is over.
+    pub match_winner: Option<String>,
+}
+
+/// An overview of the games of a player.
+#[cw_serde]
+pub struct DashboardResponse {
+    /// The games in progress.
+    pub active: Vec<GamesInfo>,
+    /// The hosts who invited the player.
+    pub pending_incoming: Vec<String>,
+    /// The guests invited by the player.
+    pub pending_outgoing: Vec<String>,
+    /// The results of the most recently completed games, latest first.
+    pub recent_results: Vec<GameResult>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::to_json_string;
+
+    #[test]
+    fn action_matches_serialization() {
+        for action in [
+            Action::Instantiate,
+            Action::Invite,
+            Action::InviteMany,
+            Action::MutualAccept,
+            Action::ExpireInvite,
+            Action::AcceptInvite,
+            Action::RejectInvite,
+            Action::Play,
+            Action::IllegalMove,
+            Action::PlaySequence,
+            Action::TransferSeat,
+            Action::Resign,
+            Action::ClaimTimeout,
+            Action::ClaimResult,
+            Action::ForceEnd,
+            Action::ImportGame,
+            Action::PurgePair,
+            Action::SetNickname,
+            Action::PostMessage,
+            Action::ClearHistory,
+            Action::UpdateConfig,
+            Action::GameOver,
+            Action::MatchOver,
+            Action::MatchConceded,
+        ] {
+            assert_eq!(
+                to_json_string(&action).unwrap(),
+                format!("\"{}\"", action.as_str())
+            );
+        }
+    }
 }
@@ -1,14 +1,203 @@
 use crate::game::{Game, Player};
-use cosmwasm_std::Addr;
-use cw_storage_plus::Map;
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Timestamp};
+use cw_storage_plus::{Item, Map};
 use serde::{Deserialize, Serialize};
 
+/// The default number of seconds after which a pending invitation can be expired by the host.
+pub const DEFAULT_INVITE_TIMEOUT: u64 = 24 * 60 * 60;
+
+/// The default number of seconds a player has to make a move.
+pub const DEFAULT_MOVE_TIMEOUT: u64 = 24 * 60 * 60;
+
+/// The configuration of the contract.
+#[cw_serde]
+pub struct Config {
+    /// The address allowed to update the configuration.
+    pub admin: Addr,
+    /// The number of seconds after which a pending invitation can be expired by the host.
+    pub invite_timeout: u64,
+    /// The number of seconds a player has to make a move.
+    pub move_timeout: u64,
+    /// Whether finished games are kept in the history of the players.
+    #[serde(default = "default_store_history")]
+    pub store_history: bool,
+    /// The maximum number of games in progress a player can have at once, unlimited if not set.
+    #[serde(default)]
+    pub max_active_per_player: Option<u32>,
+    /// The number of illegal moves in a row after which a player forfeits the game. Illegal moves are rejected if not
+    /// set.
+    #[serde(default)]
+    pub max_illegal_moves: Option<u32>,
+    /// The number of seconds a host has to wait before inviting the same guest again.
+    #[serde(default)]
+    pub invite_cooldown: u64,
+}
+
+fn default_store_history() -> bool {
+    true
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct Games {
     pub pending_invition: bool,
+    /// The time at which the pending invitation was sent.
+    pub invited_at: Option<Timestamp>,
+    /// The time at which the last invitation was sent, kept after it is accepted, rejected or expired.
+    #[serde(default)]
+    pub last_invited_at: Option<Timestamp>,
+    /// The message sent with the pending invitation, cleared once it is accepted, rejected or expired.
+    #[serde(default)]
+    pub invite_message: Option<String>,
     pub host: Player,
+    /// Whether the host chose their symbol rather than getting one assigned.
+    #[serde(default)]
+    pub host_chose_symbol: bool,
     pub current: Option<Game>,
     pub completed: Vec<Game>,
+    /// The number of games completed between the players, including those not kept or cleared from the history.
+    #[serde(default)]
+    pub total_completed: u64,
+    /// The number of games a player must win to win the match, if the players agreed on a match.
+    #[serde(default)]
+    pub match_target: Option<u32>,
+    /// The number of games of the match won by the host.
+    #[serde(default)]
+    pub host_score: u32,
+    /// The number of games of the match won by the guest.
+    #[serde(default)]
+    pub guest_score: u32,
+    /// The messages posted by the players, the oldest first.
+    #[serde(default)]
+    pub chat: Vec<(Addr, String)>,
+    /// The number of illegal moves in a row of the host in the current game.
+    #[serde(default)]
+    pub host_illegal_moves: u32,
+    /// The number of illegal moves in a row of the guest in the current game.
+    #[serde(default)]
+    pub guest_illegal_moves: u32,
+    /// The last nonce sent by the host along with a move, kept across the games of the pair so that a move can't be
+    /// replayed in the next game.
+    #[serde(default)]
+    pub host_nonce: Option<u64>,
+    /// The last nonce sent by the guest along with a move, kept across the games of the pair.
+    #[serde(default)]
+    pub guest_nonce: Option<u64>,
+    /// The total number of seconds each player can spend on their moves of a game, if the players use a time bank
+    /// rather than the move timeout.
+    #[serde(default)]
+    pub time_bank: Option<u64>,
+    /// The number of seconds left in the time bank of the host for the current game.
+    #[serde(default)]
+    pub host_time_left: u64,
+    /// The number of seconds left in the time bank of the guest for the current game.
+    #[serde(default)]
+    pub guest_time_left: u64,
+    /// The player who asked to delete the completed games, waiting for the other player to confirm.
+    #[serde(default)]
+    pub clear_requested_by: Option<Addr>,
+}
+
+/// The status of the games between two players.
+#[cw_serde]
+#[derive(Copy)]
+pub enum GameStatus {
+    /// An invitation is waiting for the guest to respond.
+    Pending,
+    /// A game is in progress.
+    Active,
+    /// No game is in progress nor pending.
+    Completed,
+}
+
+impl Games {
+    /// Creates the games of a new pair of players, without any invitation nor game, the host playing the given
+    /// symbol.
+    pub fn new(host: Player) -> Games {
+        Games {
+            pending_invition: false,
+            invited_at: None,
+            last_invited_at: None,
+            invite_message: None,
+            host,
+            host_chose_symbol: false,
+            current: None,
+            completed: vec![],
+            total_completed: 0,
+            match_target: None,
+            host_score: 0,
+            guest_score: 0,
+            chat: vec![],
+            host_illegal_moves: 0,
+            guest_illegal_moves: 0,
+            host_nonce: None,
+            guest_nonce: None,
+            time_bank: None,
+            host_time_left: 0,
+            guest_time_left: 0,
+            clear_requested_by: None,
+        }
+    }
+
+    /// Returns the status of the games between the two players.
+    pub fn status(&self) -> GameStatus {
+        if self.current.is_some() {
+            GameStatus::Active
+        } else if self.pending_invition {
+            GameStatus::Pending
+        } else {
+            GameStatus::Completed
+        }
+    }
+
+    /// Returns the number of games completed between the players. It only grows, so it tells the games of the pair
+    /// apart.
+    pub fn completed_count(&self) -> u64 {
+        // Pairs stored before the counter was added only have their history.
+        self.total_completed.max(self.completed.len() as u64)
+    }
+
+    /// Returns the role of the player who won the match, if any.
+    pub fn match_winner(&self) -> Option<Player> {
+        let target = self.match_target?;
+        if self.host_score >= target {
+            Some(self.host)
+        } else if self.guest_score >= target {
+            Some(self.host.opponent())
+        } else {
+            None
+        }
+    }
+}
+
+/// The statistics of a player.
+#[cw_serde]
+#[derive(Default)]
+pub struct PlayerStats {
+    /// The number of games the player lost by not playing within the move timeout or their time bank. These games are
+    /// not counted as any other kind of loss.
+    #[serde(default)]
+    pub timeouts: u32,
+    /// The number of games won in a row by the player, up to their last completed game. Any game not won, draws
+    /// included, ends the streak.
+    #[serde(default)]
+    pub current_streak: u32,
+    /// The highest number of games the player won in a row.
+    #[serde(default)]
+    pub longest_streak: u32,
 }
 
+pub const CONFIG: Item<Config> = Item::new("config");
+/// The number of games completed across all players.
+pub const TOTAL_COMPLETED: Item<u64> = Item::new("total_completed");
+/// The number of games completed across all players that ended in a draw.
+pub const TOTAL_DRAWS: Item<u64> = Item::new("total_draws");
+/// The number of pairs of players stored in `GAMES`.
+pub const TOTAL_PAIRS: Item<u64> = Item::new("total_pairs");
 pub const GAMES: Map<(&Addr, &Addr), Games> = Map::new("games");
+/// The statistics of the players, only stored once a player completed a game.
+pub const PLAYER_STATS: Map<&Addr, PlayerStats> = Map::new("player_stats");
+/// The number of games in progress of the players, only stored once a player started a game.
+pub const ACTIVE_GAMES: Map<&Addr, u32> = Map::new("active_games");
+/// The nicknames chosen by the players.
+pub const NICKNAMES: Map<&Addr, String> = Map::new("nicknames");